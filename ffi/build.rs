@@ -0,0 +1,14 @@
+use std::env;
+use std::path::Path;
+
+/// Generates the header into `OUT_DIR`, the test `header_is_up_to_date` compares it with the checked-in `kalkulacka.h`.
+fn main() {
+    println!("cargo:rerun-if-changed=src/lib.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+
+    let crate_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+    let out_dir = env::var("OUT_DIR").unwrap();
+    cbindgen::generate(&crate_dir)
+        .expect("Nelze vygenerovat kalkulacka.h")
+        .write_to_file(Path::new(&out_dir).join("kalkulacka.h"));
+}
@@ -0,0 +1,134 @@
+/* C API of kalkulacka_hw, generated by cbindgen from ffi/src/lib.rs. */
+
+#ifndef KALKULACKA_H
+#define KALKULACKA_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+/**
+ * Identifiers of the built-in operations, registered ones follow from `kalkulacka_operation_count()` on.
+ */
+#define KALKULACKA_PADDB 0
+
+#define KALKULACKA_PADDSB 1
+
+#define KALKULACKA_PINCB 2
+
+#define KALKULACKA_PINCSB 3
+
+#define KALKULACKA_PMULLB 4
+
+#define KALKULACKA_PADDW 5
+
+#define KALKULACKA_PADDSW 6
+
+#define KALKULACKA_PINCW 7
+
+#define KALKULACKA_PINCSW 8
+
+#define KALKULACKA_PMULLW 9
+
+#define KALKULACKA_PADDD 10
+
+#define KALKULACKA_PADDSD 11
+
+#define KALKULACKA_PINCD 12
+
+#define KALKULACKA_PINCSD 13
+
+#define KALKULACKA_PMULLD 14
+
+#define KALKULACKA_PADDQ 15
+
+#define KALKULACKA_PADDSQ 16
+
+#define KALKULACKA_PINCQ 17
+
+#define KALKULACKA_PINCSQ 18
+
+typedef enum KalkulackaStatus {
+  KALKULACKA_STATUS_OK = 0,
+  KALKULACKA_STATUS_PARSE = 1,
+  KALKULACKA_STATUS_MISSING_OPERAND = 2,
+  KALKULACKA_STATUS_INVALID_ARGUMENT = 3,
+  KALKULACKA_STATUS_UNSUPPORTED = 4,
+  KALKULACKA_STATUS_UNKNOWN_OPERATION = 5,
+  KALKULACKA_STATUS_NULL_POINTER = 6,
+  KALKULACKA_STATUS_PANIC = 7,
+} KalkulackaStatus;
+
+typedef enum KalkulackaLaneStatus {
+  KALKULACKA_LANE_STATUS_UNCHANGED = 0,
+  KALKULACKA_LANE_STATUS_OVERFLOWED = 1,
+  KALKULACKA_LANE_STATUS_SATURATED_HIGH = 2,
+  KALKULACKA_LANE_STATUS_SATURATED_LOW = 3,
+  KALKULACKA_LANE_STATUS_MASKED = 4,
+} KalkulackaLaneStatus;
+
+/**
+ * `registers[1]` and its lanes are valid only when `register_count` is 2, i.e. for PINC* which increment `mm2` too.
+ */
+typedef struct KalkulackaResult {
+  uint64_t registers[2];
+  uint8_t register_count;
+  uint8_t lane_bits[2];
+  enum KalkulackaLaneStatus lane_statuses[2][8];
+} KalkulackaResult;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Number of operations, the built-in ones and those registered by `kalkulacka_register_operations`.
+ */
+uint32_t kalkulacka_operation_count(void);
+
+/**
+ * Identifier of the operation named e.g. `"PADDSB"`, case-insensitive, or -1 if there's none.
+ */
+int32_t kalkulacka_operation_find(const char *name);
+
+/**
+ * Writes the mnemonic of the operation into `buffer`, returns the size it needs including NUL or 0 for an unknown identifier.
+ */
+size_t kalkulacka_operation_name(uint32_t operation,
+                                 char *buffer,
+                                 size_t length);
+
+/**
+ * Applies the operation to `mm1` and `mm2`, PINC* increment both and return the incremented `mm2` in `registers[1]`.
+ */
+enum KalkulackaStatus kalkulacka_calculate(uint32_t operation,
+                                           uint64_t mm1,
+                                           uint64_t mm2,
+                                           struct KalkulackaResult *result);
+
+/**
+ * Like `kalkulacka_calculate` with an AVX-512 style opmask, disabled lanes are zeroed if `zero` is set, kept from the operand otherwise.
+ */
+enum KalkulackaStatus kalkulacka_calculate_masked(uint32_t operation,
+                                                  uint64_t mm1,
+                                                  uint64_t mm2,
+                                                  uint8_t mask,
+                                                  bool zero,
+                                                  struct KalkulackaResult *result);
+
+/**
+ * Registers the operations of a file in the expression language of `--operations`, they get the next free identifiers.
+ */
+enum KalkulackaStatus kalkulacka_register_operations(const char *path);
+
+/**
+ * Writes the message of the last error on this thread into `buffer`, returns the size it needs including NUL or 0 if there was none.
+ */
+size_t kalkulacka_last_error(char *buffer,
+                             size_t length);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* KALKULACKA_H */
@@ -0,0 +1,335 @@
+//! C API of the calculator, its header `kalkulacka.h` is generated by cbindgen in `build.rs` and checked in,
+//! a test fails when it's out of date.
+//!
+//! Operations are identified by their index in [`operations`], the built-in ones come first in the order of
+//! `Operations`, so `KALKULACKA_PADDB` is always 0. Functions return a `KalkulackaStatus`, the message of the
+//! last error of the calling thread is available through `kalkulacka_last_error`. A panic never unwinds into C,
+//! the function returns `KALKULACKA_STATUS_PANIC` or its documented failure value instead.
+
+use std::cell::RefCell;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::ptr;
+use kalkulacka_hw::custom::register_file;
+use kalkulacka_hw::{calculate, operations, find, u64x1, Calculation, EitherLanes, EitherRegisters, Error, LaneFlags, LaneStatus, Mask, MaskMode};
+
+/// Identifiers of the built-in operations, registered ones follow from `kalkulacka_operation_count()` on.
+pub const KALKULACKA_PADDB: u32 = 0;
+pub const KALKULACKA_PADDSB: u32 = 1;
+pub const KALKULACKA_PINCB: u32 = 2;
+pub const KALKULACKA_PINCSB: u32 = 3;
+pub const KALKULACKA_PMULLB: u32 = 4;
+pub const KALKULACKA_PADDW: u32 = 5;
+pub const KALKULACKA_PADDSW: u32 = 6;
+pub const KALKULACKA_PINCW: u32 = 7;
+pub const KALKULACKA_PINCSW: u32 = 8;
+pub const KALKULACKA_PMULLW: u32 = 9;
+pub const KALKULACKA_PADDD: u32 = 10;
+pub const KALKULACKA_PADDSD: u32 = 11;
+pub const KALKULACKA_PINCD: u32 = 12;
+pub const KALKULACKA_PINCSD: u32 = 13;
+pub const KALKULACKA_PMULLD: u32 = 14;
+pub const KALKULACKA_PADDQ: u32 = 15;
+pub const KALKULACKA_PADDSQ: u32 = 16;
+pub const KALKULACKA_PINCQ: u32 = 17;
+pub const KALKULACKA_PINCSQ: u32 = 18;
+
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum KalkulackaStatus {
+    Ok = 0,
+    Parse = 1,
+    MissingOperand = 2,
+    InvalidArgument = 3,
+    Unsupported = 4,
+    UnknownOperation = 5,
+    NullPointer = 6,
+    Panic = 7,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub enum KalkulackaLaneStatus {
+    #[default]
+    Unchanged = 0,
+    Overflowed = 1,
+    SaturatedHigh = 2,
+    SaturatedLow = 3,
+    Masked = 4,
+}
+
+/// `registers[1]` and its lanes are valid only when `register_count` is 2, i.e. for PINC* which increment `mm2` too.
+#[repr(C)]
+#[derive(Clone, Copy, Default, Debug)]
+pub struct KalkulackaResult {
+    pub registers: [u64; 2],
+    pub register_count: u8,
+    pub lane_bits: [u8; 2],
+    pub lane_statuses: [[KalkulackaLaneStatus; 8]; 2],
+}
+
+impl From<LaneStatus> for KalkulackaLaneStatus {
+    fn from(status: LaneStatus) -> Self {
+        match status {
+            LaneStatus::Unchanged => KalkulackaLaneStatus::Unchanged,
+            LaneStatus::Overflowed => KalkulackaLaneStatus::Overflowed,
+            LaneStatus::SaturatedHigh => KalkulackaLaneStatus::SaturatedHigh,
+            LaneStatus::SaturatedLow => KalkulackaLaneStatus::SaturatedLow,
+            LaneStatus::Masked => KalkulackaLaneStatus::Masked,
+        }
+    }
+}
+
+impl From<Calculation> for KalkulackaResult {
+    fn from(calculation: Calculation) -> Self {
+        let mut result = KalkulackaResult::default();
+        let mut set = |index: usize, register: u64x1, lanes: LaneFlags| {
+            result.registers[index] = register.lane(0, 64);
+            result.lane_bits[index] = lanes.bits() as u8;
+            for (lane, status) in lanes.iter().enumerate() {
+                result.lane_statuses[index][lane] = status.into();
+            }
+        };
+
+        match (calculation.registers, calculation.lanes) {
+            (EitherRegisters::TwoRegisters(x, y), EitherLanes::TwoRegisters(x_lanes, y_lanes)) => {
+                set(0, x, x_lanes);
+                set(1, y, y_lanes);
+                result.register_count = 2;
+            }
+            (EitherRegisters::OneRegister(x), EitherLanes::OneRegister(lanes)) |
+            (EitherRegisters::OneRegister(x), EitherLanes::TwoRegisters(lanes, _)) |
+            (EitherRegisters::TwoRegisters(x, _), EitherLanes::OneRegister(lanes)) => {
+                set(0, x, lanes);
+                result.register_count = 1;
+            }
+        }
+        result
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn fail(status: KalkulackaStatus, message: String) -> KalkulackaStatus {
+    // Messages never contain NUL, but a truncated one is better than none.
+    let message = CString::new(message).unwrap_or_else(|error| {
+        let position = error.nul_position();
+        CString::new(&error.into_vec()[..position]).unwrap()
+    });
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+    status
+}
+
+fn fail_with(error: Error) -> KalkulackaStatus {
+    let status = match error {
+        Error::Parse(_) => KalkulackaStatus::Parse,
+        Error::MissingOperand(_) => KalkulackaStatus::MissingOperand,
+        Error::InvalidArgument(_) | Error::Clipboard(_) => KalkulackaStatus::InvalidArgument,
+        Error::Unsupported(_) => KalkulackaStatus::Unsupported,
+    };
+    fail(status, error.to_string())
+}
+
+/// Runs `body`, a panic is recorded as the last error and `failure` is returned instead.
+fn guard<T>(failure: T, body: impl FnOnce() -> T) -> T {
+    panic::catch_unwind(AssertUnwindSafe(body)).unwrap_or_else(|payload| {
+        let message = payload.downcast_ref::<&str>().map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_default();
+        fail(KalkulackaStatus::Panic, format!("Vnitřní chyba kalkulačky: {}", message));
+        failure
+    })
+}
+
+/// Copies `text` with a terminating NUL into `buffer` if it fits, returns the size it needs like `snprintf`.
+unsafe fn copy_string(text: &[u8], buffer: *mut c_char, length: usize) -> usize {
+    if !buffer.is_null() && length > text.len() {
+        ptr::copy_nonoverlapping(text.as_ptr() as *const c_char, buffer, text.len());
+        *buffer.add(text.len()) = 0;
+    }
+    text.len() + 1
+}
+
+/// Number of operations, the built-in ones and those registered by `kalkulacka_register_operations`.
+#[no_mangle]
+pub extern "C" fn kalkulacka_operation_count() -> u32 {
+    guard(0, || operations().len() as u32)
+}
+
+/// Identifier of the operation named e.g. `"PADDSB"`, case-insensitive, or -1 if there's none.
+///
+/// # Safety
+/// `name` must be NULL or a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn kalkulacka_operation_find(name: *const c_char) -> i32 {
+    guard(-1, || {
+        if name.is_null() {
+            return -1;
+        }
+        let name = match CStr::from_ptr(name).to_str() {
+            Ok(name) => name,
+            Err(_) => return -1,
+        };
+        find(name)
+            .and_then(|operation| operations().iter().position(|candidate| *candidate == operation))
+            .map_or(-1, |id| id as i32)
+    })
+}
+
+/// Writes the mnemonic of the operation into `buffer`, returns the size it needs including NUL or 0 for an unknown identifier.
+///
+/// # Safety
+/// `buffer` must be NULL or point to at least `length` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn kalkulacka_operation_name(operation: u32, buffer: *mut c_char, length: usize) -> usize {
+    guard(0, || match operations().get(operation as usize) {
+        Some(operation) => copy_string(operation.name().as_bytes(), buffer, length),
+        None => 0,
+    })
+}
+
+/// Applies the operation to `mm1` and `mm2`, PINC* increment both and return the incremented `mm2` in `registers[1]`.
+///
+/// # Safety
+/// `result` must be NULL or point to a writable `KalkulackaResult`.
+#[no_mangle]
+pub unsafe extern "C" fn kalkulacka_calculate(operation: u32, mm1: u64, mm2: u64, result: *mut KalkulackaResult) -> KalkulackaStatus {
+    guard(KalkulackaStatus::Panic, || calculate_with(operation, mm1, mm2, None, result))
+}
+
+/// Like `kalkulacka_calculate` with an AVX-512 style opmask, disabled lanes are zeroed if `zero` is set, kept from the operand otherwise.
+///
+/// # Safety
+/// `result` must be NULL or point to a writable `KalkulackaResult`.
+#[no_mangle]
+pub unsafe extern "C" fn kalkulacka_calculate_masked(operation: u32, mm1: u64, mm2: u64, mask: u8, zero: bool, result: *mut KalkulackaResult) -> KalkulackaStatus {
+    let mode = if zero { MaskMode::Zero } else { MaskMode::Merge };
+    guard(KalkulackaStatus::Panic, || calculate_with(operation, mm1, mm2, Some(Mask { bits: mask, mode }), result))
+}
+
+unsafe fn calculate_with(operation: u32, mm1: u64, mm2: u64, mask: Option<Mask>, result: *mut KalkulackaResult) -> KalkulackaStatus {
+    if result.is_null() {
+        return fail(KalkulackaStatus::NullPointer, "Ukazatel na výsledek je NULL.".to_string());
+    }
+    let operation = match operations().get(operation as usize) {
+        Some(operation) => *operation,
+        None => return fail(KalkulackaStatus::UnknownOperation, format!("Neznámá operace s číslem {}.", operation)),
+    };
+
+    match calculate(u64x1::new(mm1), Some(u64x1::new(mm2)), operation, mask) {
+        Ok(calculation) => {
+            *result = calculation.into();
+            KalkulackaStatus::Ok
+        }
+        Err(error) => fail_with(error),
+    }
+}
+
+/// Registers the operations of a file in the expression language of `--operations`, they get the next free identifiers.
+///
+/// # Safety
+/// `path` must be NULL or a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn kalkulacka_register_operations(path: *const c_char) -> KalkulackaStatus {
+    guard(KalkulackaStatus::Panic, || {
+        if path.is_null() {
+            return fail(KalkulackaStatus::NullPointer, "Cesta k souboru je NULL.".to_string());
+        }
+        let path = match CStr::from_ptr(path).to_str() {
+            Ok(path) => path,
+            Err(_) => return fail(KalkulackaStatus::InvalidArgument, "Cesta k souboru není platné UTF-8.".to_string()),
+        };
+
+        match register_file(Path::new(path)) {
+            Ok(_) => KalkulackaStatus::Ok,
+            Err(error) => fail_with(error),
+        }
+    })
+}
+
+/// Writes the message of the last error on this thread into `buffer`, returns the size it needs including NUL or 0 if there was none.
+///
+/// # Safety
+/// `buffer` must be NULL or point to at least `length` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn kalkulacka_last_error(buffer: *mut c_char, length: usize) -> usize {
+    guard(0, || LAST_ERROR.with(|last| match &*last.borrow() {
+        Some(message) => copy_string(message.as_bytes(), buffer, length),
+        None => 0,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kalkulacka_hw::calculations::Lanes;
+    use kalkulacka_hw::{register, Metadata, Operation, Operations};
+
+    const HEADER: &str = include_str!("../kalkulacka.h");
+
+    #[test]
+    fn header_is_up_to_date() {
+        let generated = include_str!(concat!(env!("OUT_DIR"), "/kalkulacka.h"));
+        assert!(HEADER == generated, "kalkulacka.h je zastaralá, nahraďte ji souborem {}/kalkulacka.h", env!("OUT_DIR"));
+    }
+
+    #[test]
+    fn header_matches_operations() {
+        for (id, operation) in Operations::ALL.iter().enumerate() {
+            let define = format!("#define KALKULACKA_{} {}\n", operation, id);
+            assert!(HEADER.contains(&define), "chybí {}", define.trim());
+        }
+    }
+
+    #[test]
+    fn calculates_through_identifiers() {
+        let id = unsafe { kalkulacka_operation_find(b"paddsb\0".as_ptr() as *const c_char) };
+        assert_eq!(id, Operations::PADDSB as i32);
+
+        let mut result = KalkulackaResult::default();
+        let status = unsafe { kalkulacka_calculate(id as u32, 0xFFFF, 0x0101, &mut result) };
+        assert_eq!(status, KalkulackaStatus::Ok);
+        assert_eq!((result.registers[0], result.register_count, result.lane_bits[0]), (0xFFFF, 1, 8));
+        assert_eq!(result.lane_statuses[0][..3], [KalkulackaLaneStatus::SaturatedHigh, KalkulackaLaneStatus::SaturatedHigh, KalkulackaLaneStatus::Unchanged]);
+
+        let status = unsafe { kalkulacka_calculate(u32::MAX, 0, 0, &mut result) };
+        assert_eq!(status, KalkulackaStatus::UnknownOperation);
+        let mut message = [0 as c_char; 64];
+        let length = unsafe { kalkulacka_last_error(message.as_mut_ptr(), message.len()) };
+        assert!(length > 1 && length <= message.len());
+    }
+
+    #[test]
+    fn panics_become_a_status() {
+        struct Panicking;
+
+        impl Operation for Panicking {
+            fn name(&self) -> &str {
+                "PPANICB"
+            }
+
+            fn metadata(&self) -> &Metadata {
+                Operations::PADDB.metadata()
+            }
+
+            fn evaluate(&self, _: u64x1, _: u64x1) -> Result<(Lanes, Option<Lanes>), Error> {
+                panic!("rozbitá operace")
+            }
+        }
+
+        register(Box::new(Panicking)).unwrap();
+        let id = unsafe { kalkulacka_operation_find(b"PPANICB\0".as_ptr() as *const c_char) };
+        let mut result = KalkulackaResult::default();
+        let status = unsafe { kalkulacka_calculate(id as u32, 0, 0, &mut result) };
+        assert_eq!(status, KalkulackaStatus::Panic);
+
+        let mut message = [0 as c_char; 128];
+        unsafe { kalkulacka_last_error(message.as_mut_ptr(), message.len()) };
+        let message = unsafe { CStr::from_ptr(message.as_ptr()) }.to_str().unwrap();
+        assert!(message.ends_with("rozbitá operace"), "{}", message);
+    }
+}
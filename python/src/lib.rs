@@ -0,0 +1,289 @@
+//! Python module `kalkulacka`, e.g.
+//!
+//! ```python
+//! import kalkulacka
+//! calculation = kalkulacka.calculate("PADDSB", 0xFFFF, 0x0101)
+//! [lane.status for lane in calculation.lanes[0]]
+//! ```
+
+use std::path::Path;
+use pyo3::create_exception;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use kalkulacka_hw::custom::register_file;
+use kalkulacka_hw::{self as core, Calculation as CoreCalculation, EitherLanes, EitherRegisters, LaneFlags, LaneStatus, Mask, MaskMode};
+
+create_exception!(kalkulacka, Error, PyValueError, "Chyba kalkulačky, zpráva je česky.");
+
+fn to_py(error: core::Error) -> PyErr {
+    Error::new_err(error.to_string())
+}
+
+/// A built-in or registered operation.
+#[pyclass(frozen, module = "kalkulacka")]
+#[derive(Clone)]
+pub struct Operation(&'static dyn core::Operation);
+
+#[pymethods]
+impl Operation {
+    #[getter]
+    fn name(&self) -> &str {
+        self.0.name()
+    }
+
+    #[getter]
+    fn description(&self) -> String {
+        self.0.metadata().description.to_string()
+    }
+
+    #[getter]
+    fn pseudocode(&self) -> String {
+        self.0.metadata().pseudocode.to_string()
+    }
+
+    /// Width of the lanes of the operands in bits.
+    #[getter]
+    fn bits(&self) -> usize {
+        self.0.metadata().width.bits()
+    }
+
+    #[getter]
+    fn operands(&self) -> usize {
+        self.0.metadata().operands
+    }
+
+    fn __eq__(&self, other: &Self) -> bool {
+        *self.0 == *other.0
+    }
+
+    fn __str__(&self) -> &str {
+        self.0.name()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Operation('{}')", self.0.name())
+    }
+}
+
+/// Built-in operations followed by the registered ones.
+#[pyfunction]
+fn operations() -> Vec<Operation> {
+    core::operations().into_iter().map(Operation).collect()
+}
+
+#[pyfunction]
+fn find(name: &str) -> Option<Operation> {
+    core::find(name).map(Operation)
+}
+
+/// Registers the operations of a file in the expression language of `--operations`.
+#[pyfunction]
+fn register_operations(path: &str) -> PyResult<Vec<Operation>> {
+    register_file(Path::new(path)).map(|operations| operations.into_iter().map(Operation).collect()).map_err(to_py)
+}
+
+macro_rules! lane_type {
+    ($($name:ident: $lane:ty, $bits:expr;)*) => {$(
+        /// 64-bit register split into lanes, created from an integer or a list of lanes, lane 0 first.
+        #[pyclass(frozen, module = "kalkulacka")]
+        #[allow(non_camel_case_types)]
+        #[derive(Clone, Copy)]
+        pub struct $name(core::u64x1);
+
+        #[pymethods]
+        impl $name {
+            #[new]
+            fn new(value: Register) -> Self {
+                Self(value.0)
+            }
+
+            #[staticmethod]
+            fn from_lanes(lanes: [$lane; 64 / $bits]) -> Self {
+                Self(lanes.iter().enumerate().fold(core::u64x1::default(), |value, (index, lane)| value.with_lane(index, $bits, *lane as u64)))
+            }
+
+            #[getter]
+            fn value(&self) -> u64 {
+                self.0.lane(0, 64)
+            }
+
+            #[getter]
+            fn lanes(&self) -> Vec<u64> {
+                (0..64 / $bits).map(|index| self.0.lane(index, $bits)).collect()
+            }
+
+            #[getter]
+            fn signed_lanes(&self) -> Vec<i64> {
+                (0..64 / $bits).map(|index| signed(self.0.lane(index, $bits), $bits)).collect()
+            }
+
+            fn __int__(&self) -> u64 {
+                self.value()
+            }
+
+            fn __eq__(&self, other: Register) -> bool {
+                self.0 == other.0
+            }
+
+            fn __repr__(&self) -> String {
+                format!("{}.from_lanes({:?})", stringify!($name), self.lanes())
+            }
+        }
+    )*};
+}
+
+lane_type! {
+    u64x1: u64, 64;
+    u32x2: u32, 32;
+    u16x4: u16, 16;
+    u8x8: u8, 8;
+}
+
+/// Any of the lane types or an integer, Python integers must fit into 64 bits.
+struct Register(core::u64x1);
+
+impl<'py> FromPyObject<'py> for Register {
+    fn extract_bound(object: &Bound<'py, PyAny>) -> PyResult<Self> {
+        if let Ok(value) = object.extract::<u64x1>() {
+            return Ok(Register(value.0));
+        }
+        if let Ok(value) = object.extract::<u32x2>() {
+            return Ok(Register(value.0));
+        }
+        if let Ok(value) = object.extract::<u16x4>() {
+            return Ok(Register(value.0));
+        }
+        if let Ok(value) = object.extract::<u8x8>() {
+            return Ok(Register(value.0));
+        }
+        Ok(Register(core::u64x1::new(object.extract()?)))
+    }
+}
+
+fn signed(value: u64, bits: usize) -> i64 {
+    ((value << (64 - bits)) as i64) >> (64 - bits)
+}
+
+/// A lane of a result register.
+#[pyclass(frozen, get_all, module = "kalkulacka")]
+#[derive(Clone)]
+pub struct Lane {
+    index: usize,
+    bits: usize,
+    value: u64,
+    signed: i64,
+    /// One of `unchanged`, `overflowed`, `saturated_high`, `saturated_low` and `masked`.
+    status: &'static str,
+}
+
+#[pymethods]
+impl Lane {
+    fn __repr__(&self) -> String {
+        format!("Lane(index={}, bits={}, value={:#X}, status='{}')", self.index, self.bits, self.value, self.status)
+    }
+}
+
+fn lanes(register: core::u64x1, flags: LaneFlags) -> Vec<Lane> {
+    flags.iter().enumerate().map(|(index, status)| {
+        let value = register.lane(index, flags.bits());
+        Lane {
+            index,
+            bits: flags.bits(),
+            value,
+            signed: signed(value, flags.bits()),
+            status: match status {
+                LaneStatus::Unchanged => "unchanged",
+                LaneStatus::Overflowed => "overflowed",
+                LaneStatus::SaturatedHigh => "saturated_high",
+                LaneStatus::SaturatedLow => "saturated_low",
+                LaneStatus::Masked => "masked",
+            },
+        }
+    }).collect()
+}
+
+/// Result of `calculate`, PINC* produce two registers, the incremented `mm2` is the second one.
+#[pyclass(frozen, get_all, module = "kalkulacka")]
+pub struct Calculation {
+    registers: Vec<u64>,
+    lanes: Vec<Vec<Lane>>,
+}
+
+#[pymethods]
+impl Calculation {
+    /// The first result register, the only one for most operations.
+    #[getter]
+    fn result(&self) -> u64 {
+        self.registers[0]
+    }
+
+    fn __repr__(&self) -> String {
+        let registers: Vec<String> = self.registers.iter().map(|register| format!("{:#018X}", register)).collect();
+        format!("Calculation([{}])", registers.join(", "))
+    }
+}
+
+impl From<CoreCalculation> for Calculation {
+    fn from(calculation: CoreCalculation) -> Self {
+        let registers = match calculation.registers {
+            EitherRegisters::OneRegister(x) => vec![x],
+            EitherRegisters::TwoRegisters(x, y) => vec![x, y],
+        };
+        let flags = match calculation.lanes {
+            EitherLanes::OneRegister(x) => vec![x],
+            EitherLanes::TwoRegisters(x, y) => vec![x, y],
+        };
+        Calculation {
+            registers: registers.iter().map(|register| register.lane(0, 64)).collect(),
+            lanes: registers.into_iter().zip(flags).map(|(register, flags)| lanes(register, flags)).collect(),
+        }
+    }
+}
+
+/// An operation object or its name.
+enum OperationArgument {
+    Operation(Operation),
+    Name(String),
+}
+
+impl<'py> FromPyObject<'py> for OperationArgument {
+    fn extract_bound(object: &Bound<'py, PyAny>) -> PyResult<Self> {
+        match object.extract::<Operation>() {
+            Ok(operation) => Ok(OperationArgument::Operation(operation)),
+            Err(_) => Ok(OperationArgument::Name(object.extract()?)),
+        }
+    }
+}
+
+/// Applies the operation, `mm2` may be left out only for operations without a source operand.
+/// With `mask` only the lanes with their bit set are computed, the others are zeroed if `zero` is set, kept from the operand otherwise.
+#[pyfunction]
+#[pyo3(signature = (operation, mm1, mm2 = None, mask = None, zero = false))]
+fn calculate(operation: OperationArgument, mm1: Register, mm2: Option<Register>, mask: Option<u8>, zero: bool) -> PyResult<Calculation> {
+    let operation = match operation {
+        OperationArgument::Operation(operation) => operation.0,
+        OperationArgument::Name(name) => name.parse::<core::Operations>().map(core::builtin)
+            .or_else(|error| core::find(&name).ok_or(error))
+            .map_err(to_py)?,
+    };
+    let mask = mask.map(|bits| Mask { bits, mode: if zero { MaskMode::Zero } else { MaskMode::Merge } });
+
+    core::calculate(mm1.0, mm2.map(|mm2| mm2.0), operation, mask).map(Calculation::from).map_err(to_py)
+}
+
+#[pymodule]
+fn kalkulacka(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add("Error", module.py().get_type::<Error>())?;
+    module.add_class::<Operation>()?;
+    module.add_class::<u64x1>()?;
+    module.add_class::<u32x2>()?;
+    module.add_class::<u16x4>()?;
+    module.add_class::<u8x8>()?;
+    module.add_class::<Lane>()?;
+    module.add_class::<Calculation>()?;
+    module.add_function(wrap_pyfunction!(operations, module)?)?;
+    module.add_function(wrap_pyfunction!(find, module)?)?;
+    module.add_function(wrap_pyfunction!(register_operations, module)?)?;
+    module.add_function(wrap_pyfunction!(calculate, module)?)?;
+    Ok(())
+}
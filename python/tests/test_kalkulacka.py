@@ -0,0 +1,66 @@
+"""Run after `maturin develop` in python/, or with the built library copied as kalkulacka.so on PYTHONPATH."""
+
+import os
+import tempfile
+import unittest
+
+import kalkulacka
+
+
+class CalculateTest(unittest.TestCase):
+    def test_saturating_lanes(self):
+        calculation = kalkulacka.calculate("PADDSB", 0xFFFF, 0x0101)
+        self.assertEqual(calculation.result, 0xFFFF)
+        statuses = [lane.status for lane in calculation.lanes[0]]
+        self.assertEqual(statuses, ["saturated_high"] * 2 + ["unchanged"] * 6)
+
+    def test_lane_types_as_operands(self):
+        calculation = kalkulacka.calculate(kalkulacka.find("paddw"), kalkulacka.u16x4.from_lanes([1, 2, 3, 0xFFFF]), kalkulacka.u16x4.from_lanes([1, 1, 1, 1]))
+        self.assertEqual(kalkulacka.u16x4(calculation.result).lanes, [2, 3, 4, 0])
+        self.assertEqual(calculation.lanes[0][3].status, "overflowed")
+        self.assertEqual(kalkulacka.u16x4(calculation.result).signed_lanes, [2, 3, 4, 0])
+
+    def test_widening(self):
+        calculation = kalkulacka.calculate("PMULLD", kalkulacka.u32x2.from_lanes([0x10000, 2]), kalkulacka.u32x2.from_lanes([0x10000, 3]))
+        self.assertEqual(calculation.registers, [0x100000000])
+        self.assertEqual(calculation.lanes[0][0].bits, 64)
+
+    def test_increment_writes_both_registers(self):
+        calculation = kalkulacka.calculate("PINCSB", kalkulacka.u8x8.from_lanes([0xFF, 1, 0, 0, 0, 0, 0, 0]))
+        self.assertEqual(len(calculation.registers), 2)
+        self.assertEqual(kalkulacka.u8x8(calculation.registers[0]).lanes[:2], [0xFF, 2])
+        self.assertEqual(calculation.lanes[0][0].status, "saturated_high")
+
+    def test_mask(self):
+        calculation = kalkulacka.calculate("PADDB", 0x0101, 0x0101, mask=0x01, zero=True)
+        self.assertEqual(calculation.result, 0x02)
+        self.assertEqual(calculation.lanes[0][1].status, "masked")
+
+    def test_errors(self):
+        with self.assertRaises(kalkulacka.Error):
+            kalkulacka.calculate("PSUBB", 1, 1)
+        with self.assertRaises(kalkulacka.Error):
+            kalkulacka.calculate("PADDB", 1)
+        self.assertEqual(kalkulacka.calculate("PINCQ", 1).result, 2)
+
+
+class OperationsTest(unittest.TestCase):
+    def test_all_operations(self):
+        names = [operation.name for operation in kalkulacka.operations()]
+        self.assertEqual(names[:3], ["PADDB", "PADDSB", "PINCB"])
+        for operation in kalkulacka.operations():
+            self.assertIn(operation.bits, (8, 16, 32, 64))
+            self.assertTrue(operation.description)
+
+    def test_register_operations(self):
+        with tempfile.TemporaryDirectory() as directory:
+            path = os.path.join(directory, "operations.txt")
+            with open(path, "w") as file:
+                file.write("PAVGB b = (a + b + 1) >> 1\n")
+            kalkulacka.register_operations(path)
+        self.assertEqual(kalkulacka.calculate("PAVGB", 0x0301, 0x0102).result, 0x0202)
+        self.assertEqual(kalkulacka.operations()[-1], kalkulacka.find("pavgb"))
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -1,72 +1,112 @@
 use crate::types::*;
+use crate::operations::Operation;
+use crate::error::Error;
+use core::fmt;
+use alloc::format;
+use fmt::{Display, Formatter};
 
 #[cfg_attr(feature = "debug", derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum EitherRegisters {
     OneRegister(u64x1),
     TwoRegisters(u64x1, u64x1),
 }
 
-pub fn calculate(mm1: u64x1, mm2: u64x1, operation: &Operations) -> EitherRegisters {
-    match operation {
-        Operations::PADDB | Operations::PADDSB | Operations::PINCB | Operations::PINCSB | Operations::PMULLB => {
-            let mm1: u8x8 = mm1.into();
-            let mm2: u8x8 = mm2.into();
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EitherLanes {
+    OneRegister(LaneFlags),
+    TwoRegisters(LaneFlags, LaneFlags),
+}
 
-            match operation {
-                Operations::PADDB => mm1.overflowing_add(&mm2).into(),
-                Operations::PADDSB => mm1.saturating_add(&mm2).into(),
-                Operations::PINCB => (mm1.overflowing_increment(), mm2.overflowing_increment()).into(),
-                Operations::PINCSB => (mm1.saturating_increment(), mm2.saturating_increment()).into(),
-                Operations::PMULLB => mm1.multiply(&mm2).into(),
-                _ => { unreachable!() }
-            }
-        }
-        Operations::PADDW | Operations::PADDSW | Operations::PINCW | Operations::PINCSW | Operations::PMULLW => {
-            let mm1: u16x4 = mm1.into();
-            let mm2: u16x4 = mm2.into();
+#[cfg_attr(feature = "debug", derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Calculation {
+    pub registers: EitherRegisters,
+    pub lanes: EitherLanes,
+}
 
-            match operation {
-                Operations::PADDW => mm1.overflowing_add(&mm2).into(),
-                Operations::PADDSW => mm1.saturating_add(&mm2).into(),
-                Operations::PINCW => (mm1.overflowing_increment(), mm2.overflowing_increment()).into(),
-                Operations::PINCSW => (mm1.saturating_increment(), mm2.saturating_increment()).into(),
-                Operations::PMULLW => mm1.multiply(&mm2).into(),
-                _ => { unreachable!() }
-            }
-        }
-        Operations::PADDD | Operations::PADDSD | Operations::PINCD | Operations::PINCSD | Operations::PMULLD => {
-            let mm1: u32x2 = mm1.into();
-            let mm2: u32x2 = mm2.into();
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(any(feature = "debug", feature = "gui"), derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MaskMode {
+    Merge,
+    Zero,
+}
+
+/// AVX-512 style opmask, bit `i` enables lane `i` of the result.
+#[derive(Clone, Copy)]
+#[cfg_attr(any(feature = "debug", feature = "gui"), derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Mask {
+    pub bits: u8,
+    pub mode: MaskMode,
+}
 
-            match operation {
-                Operations::PADDD => mm1.overflowing_add(&mm2).into(),
-                Operations::PADDSD => mm1.saturating_add(&mm2).into(),
-                Operations::PINCD => (mm1.overflowing_increment(), mm2.overflowing_increment()).into(),
-                Operations::PINCSD => (mm1.saturating_increment(), mm2.saturating_increment()).into(),
-                Operations::PMULLD => mm1.multiply(&mm2).into(),
-                _ => { unreachable!() }
-            }
+impl Mask {
+    fn apply(&self, original: u64x1, result: u64x1, mut lanes: LaneFlags) -> (u64x1, LaneFlags) {
+        let mut result = result;
+        for index in (0..lanes.lanes()).filter(|index| self.bits & (1 << index) == 0) {
+            let lane = match self.mode {
+                MaskMode::Merge => original.lane(index, lanes.bits()),
+                MaskMode::Zero => 0,
+            };
+            result = result.with_lane(index, lanes.bits(), lane);
+            lanes.set_status(index, LaneStatus::Masked);
         }
-        Operations::PADDQ => mm1.overflowing_add(&mm2).into(),
-        Operations::PADDSQ => mm1.saturating_add(&mm2).into(),
-        Operations::PINCQ | Operations::PINCSQ => {
-            match operation {
-                Operations::PINCQ => (mm1.overflowing_increment(), mm2.overflowing_increment()).into(),
-                Operations::PINCSQ => (mm1.saturating_increment(), mm2.saturating_increment()).into(),
-                _ => { unreachable!() }
-            }
+        (result, lanes)
+    }
+}
+
+impl Display for Mask {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{{k={:02X}}}", self.bits)?;
+        if self.mode == MaskMode::Zero {
+            f.write_str("{z}")?;
         }
+        Ok(())
     }
 }
 
-impl<T> From<T> for EitherRegisters where T: Into<u64x1> {
-    fn from(data: T) -> Self {
-        EitherRegisters::OneRegister(data.into())
+/// `mm2` may be left out only for operations that don't read it (`PINC*`), it's zero then.
+pub fn calculate(mm1: u64x1, mm2: Option<u64x1>, operation: &dyn Operation, mask: Option<Mask>) -> Result<Calculation, Error> {
+    let mm2 = match mm2 {
+        Some(mm2) => mm2,
+        None if operation.requires_mm2() => {
+            return Err(Error::MissingOperand(format!("Operace {} vyžaduje zdrojový registr.", operation)));
+        }
+        None => u64x1::default(),
+    };
+
+    let (x, y) = operation.evaluate(mm1, mm2)?;
+    let (x, y) = match mask {
+        None => (x, y),
+        Some(mask) => (mask.apply(mm1, x.0, x.1), y.map(|(y, y_lanes)| mask.apply(mm2, y, y_lanes))),
+    };
+
+    Ok(match y {
+        None => x.into(),
+        Some(y) => (x, y).into(),
+    })
+}
+
+/// A result register with the status of each of its lanes.
+pub type Lanes = (u64x1, LaneFlags);
+
+impl<T> From<(T, LaneFlags)> for Calculation where T: Into<u64x1> {
+    fn from(data: (T, LaneFlags)) -> Self {
+        Calculation {
+            registers: EitherRegisters::OneRegister(data.0.into()),
+            lanes: EitherLanes::OneRegister(data.1),
+        }
     }
 }
 
-impl<T, Z> From<(T, Z)> for EitherRegisters where T: Into<u64x1>, Z: Into<u64x1> {
-    fn from(data: (T, Z)) -> Self {
-        EitherRegisters::TwoRegisters(data.0.into(), data.1.into())
+impl<T, Z> From<((T, LaneFlags), (Z, LaneFlags))> for Calculation where T: Into<u64x1>, Z: Into<u64x1> {
+    fn from(data: ((T, LaneFlags), (Z, LaneFlags))) -> Self {
+        Calculation {
+            registers: EitherRegisters::TwoRegisters((data.0).0.into(), (data.1).0.into()),
+            lanes: EitherLanes::TwoRegisters((data.0).1, (data.1).1),
+        }
     }
-}
\ No newline at end of file
+}
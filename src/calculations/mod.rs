@@ -1,3 +1,6 @@
+#[allow(clippy::module_inception)]
 mod calculations;
+#[cfg(test)]
+mod tests;
 
-pub use calculations::*;
\ No newline at end of file
+pub use calculations::*;
@@ -0,0 +1,238 @@
+use crate::types::*;
+use crate::calculations::*;
+use crate::utils::XorShift64;
+use crate::error::Error;
+
+const RANDOM_SAMPLES: usize = 20_000;
+
+#[derive(Clone, Copy)]
+enum Kind {
+    Add,
+    SaturatingAdd,
+    Increment,
+    SaturatingIncrement,
+    Multiply,
+}
+
+fn describe(operation: Operations) -> (usize, Kind) {
+    match operation {
+        Operations::PADDB => (8, Kind::Add),
+        Operations::PADDSB => (8, Kind::SaturatingAdd),
+        Operations::PINCB => (8, Kind::Increment),
+        Operations::PINCSB => (8, Kind::SaturatingIncrement),
+        Operations::PMULLB => (8, Kind::Multiply),
+        Operations::PADDW => (16, Kind::Add),
+        Operations::PADDSW => (16, Kind::SaturatingAdd),
+        Operations::PINCW => (16, Kind::Increment),
+        Operations::PINCSW => (16, Kind::SaturatingIncrement),
+        Operations::PMULLW => (16, Kind::Multiply),
+        Operations::PADDD => (32, Kind::Add),
+        Operations::PADDSD => (32, Kind::SaturatingAdd),
+        Operations::PINCD => (32, Kind::Increment),
+        Operations::PINCSD => (32, Kind::SaturatingIncrement),
+        Operations::PMULLD => (32, Kind::Multiply),
+        Operations::PADDQ => (64, Kind::Add),
+        Operations::PADDSQ => (64, Kind::SaturatingAdd),
+        Operations::PINCQ => (64, Kind::Increment),
+        Operations::PINCSQ => (64, Kind::SaturatingIncrement),
+    }
+}
+
+fn mask(bits: usize) -> u128 {
+    (1 << bits) - 1
+}
+
+fn lane(register: u64, index: usize, bits: usize) -> u128 {
+    (register as u128 >> (index * bits)) & mask(bits)
+}
+
+/// Scalar model computing one lane at a time in `u128`, so no carry can be lost.
+fn add(x: u64, y: u64, bits: usize, saturating: bool) -> (u64, Vec<LaneStatus>) {
+    let mut result = 0;
+    let mut statuses = Vec::new();
+
+    for index in 0..64 / bits {
+        let sum = lane(x, index, bits) + lane(y, index, bits);
+        let (value, status) = match (sum > mask(bits), saturating) {
+            (false, _) => (sum, LaneStatus::Unchanged),
+            (true, false) => (sum & mask(bits), LaneStatus::Overflowed),
+            (true, true) => (mask(bits), LaneStatus::SaturatedHigh),
+        };
+        result |= value << (index * bits);
+        statuses.push(status);
+    }
+
+    (result as u64, statuses)
+}
+
+fn ones(bits: usize) -> u64 {
+    (0..64 / bits).fold(0, |register, index| register | 1 << (index * bits))
+}
+
+/// Registers and lane statuses the operation should produce, the second register only for `PINC*`.
+fn reference(operation: Operations, x: u64, y: u64) -> Vec<(u64, Vec<LaneStatus>)> {
+    let (bits, kind) = describe(operation);
+
+    match kind {
+        Kind::Add => vec![add(x, y, bits, false)],
+        Kind::SaturatingAdd => vec![add(x, y, bits, true)],
+        Kind::Increment => vec![add(x, ones(bits), bits, false), add(y, ones(bits), bits, false)],
+        Kind::SaturatingIncrement => vec![add(x, ones(bits), bits, true), add(y, ones(bits), bits, true)],
+        Kind::Multiply => {
+            let wide = bits * 2;
+            let result = (0..64 / wide).fold(0, |result, index| {
+                result | (lane(x, index * 2, bits) * lane(y, index * 2, bits)) << (index * wide)
+            });
+            vec![(result as u64, vec![LaneStatus::Unchanged; 64 / wide])]
+        }
+    }
+}
+
+fn check(operation: Operations, x: u64, y: u64) {
+    let calculation = calculate(u64x1::new(x), Some(u64x1::new(y)), &operation, None).unwrap();
+    let registers = match calculation.registers {
+        EitherRegisters::OneRegister(a) => vec![a],
+        EitherRegisters::TwoRegisters(a, b) => vec![a, b],
+    };
+    let lanes = match calculation.lanes {
+        EitherLanes::OneRegister(a) => vec![a],
+        EitherLanes::TwoRegisters(a, b) => vec![a, b],
+    };
+    let actual = registers.iter()
+        .zip(lanes.iter())
+        .map(|(register, lanes)| (register.lane(0, 64), lanes.iter().collect::<Vec<_>>()))
+        .collect::<Vec<_>>();
+    let expected = reference(operation, x, y);
+
+    assert!(
+        actual == expected,
+        "{} {:016X}, {:016X}: expected {}, got {}",
+        operation, x, y, describe_result(&expected), describe_result(&actual),
+    );
+}
+
+fn describe_result(result: &[(u64, Vec<LaneStatus>)]) -> String {
+    result.iter()
+        .map(|(register, statuses)| {
+            let statuses = statuses.iter().map(LaneStatus::to_string).collect::<Vec<_>>();
+            format!("{:016X} [{}]", register, statuses.join(", "))
+        })
+        .collect::<Vec<_>>()
+        .join(" and ")
+}
+
+/// Lanes are picked from the overflow and saturation edges half of the time.
+fn random_register(random: &mut XorShift64, bits: usize) -> u64 {
+    let max = mask(bits) as u64;
+    let edges = [0, 1, max, max - 1, max >> 1, (max >> 1) + 1];
+
+    (0..64 / bits).fold(0, |register, index| {
+        let value = random.next_u64();
+        let lane = if value & 1 == 0 { edges[(value >> 1) as usize % edges.len()] } else { value >> 8 & max };
+        register | lane << (index * bits)
+    })
+}
+
+fn check_randomly(bits: usize, seed: u64) {
+    let mut random = XorShift64::new(seed);
+    for operation in Operations::ALL.iter().filter(|operation| describe(**operation).0 == bits) {
+        for _ in 0..RANDOM_SAMPLES {
+            let (x, y) = (random_register(&mut random, bits), random_register(&mut random, bits));
+            check(*operation, x, y);
+        }
+    }
+}
+
+#[test]
+fn byte_operations_match_reference_for_every_lane_pair() {
+    for x in 0..=255u64 {
+        for y in 0..=255u64 {
+            // Lane i holds (x + i, y + 3i), so every lane sees every pair of values.
+            let (a, b) = (0..8).fold((0, 0), |(a, b), index| {
+                (a | ((x + index) & 0xFF) << (index * 8), b | ((y + 3 * index) & 0xFF) << (index * 8))
+            });
+            for operation in Operations::ALL.iter().filter(|operation| describe(**operation).0 == 8) {
+                check(*operation, a, b);
+            }
+        }
+    }
+}
+
+#[test]
+fn word_operations_match_reference() {
+    check_randomly(16, 0x1616);
+}
+
+#[test]
+fn doubleword_operations_match_reference() {
+    check_randomly(32, 0x3232);
+}
+
+#[test]
+fn quadword_operations_match_reference() {
+    check_randomly(64, 0x6464);
+}
+
+#[test]
+fn every_operation_is_covered() {
+    for operation in Operations::ALL.iter() {
+        check(*operation, u64::MAX, 1);
+        check(*operation, 0, 0);
+    }
+}
+
+#[test]
+fn pmulld_multiplies_lane_zero() {
+    let calculation = calculate(u64x1::new(0x0000_0002_0000_0003), Some(u64x1::new(0x0000_0005_0000_0007)), &Operations::PMULLD, None).unwrap();
+
+    match calculation.registers {
+        EitherRegisters::OneRegister(result) => assert_eq!(result.lane(0, 64), 21),
+        EitherRegisters::TwoRegisters(..) => panic!("PMULLD writes one register"),
+    }
+}
+
+#[test]
+fn saturation_and_overflow_edges() {
+    check(Operations::PADDSB, 0xFF, 0x01);
+    check(Operations::PADDSW, 0xFFFF_0000_8000_7FFF, 0x0001_FFFF_8000_8000);
+    check(Operations::PADDD, 0xFFFF_FFFF_FFFF_FFFF, 0x0000_0001_0000_0001);
+    check(Operations::PINCSQ, u64::MAX, u64::MAX - 1);
+    check(Operations::PINCQ, u64::MAX, 0);
+}
+
+#[test]
+fn masked_lanes_are_merged_or_zeroed() {
+    let mut random = XorShift64::new(0x4D41534B);
+
+    for _ in 0..RANDOM_SAMPLES {
+        let (x, y) = (random_register(&mut random, 16), random_register(&mut random, 16));
+        let bits = random.next_u64() as u8;
+        let unmasked = reference(Operations::PADDW, x, y)[0].0;
+
+        for mode in [MaskMode::Merge, MaskMode::Zero].iter() {
+            let calculation = calculate(u64x1::new(x), Some(u64x1::new(y)), &Operations::PADDW, Some(Mask { bits, mode: *mode })).unwrap();
+            let (result, lanes) = match (calculation.registers, calculation.lanes) {
+                (EitherRegisters::OneRegister(result), EitherLanes::OneRegister(lanes)) => (result, lanes),
+                _ => panic!("PADDW writes one register"),
+            };
+
+            for index in 0..4 {
+                let expected = match (bits >> index & 1 == 1, mode) {
+                    (true, _) => u64x1::new(unmasked).lane(index, 16),
+                    (false, MaskMode::Merge) => u64x1::new(x).lane(index, 16),
+                    (false, MaskMode::Zero) => 0,
+                };
+                assert_eq!(result.lane(index, 16), expected, "PADDW {:016X}, {:016X} with mask {:02X}, lane {}", x, y, bits, index);
+                assert!((lanes.status(index) == LaneStatus::Masked) != (bits >> index & 1 == 1));
+            }
+        }
+    }
+}
+
+#[test]
+fn missing_source_is_an_error() {
+    let error = calculate(u64x1::new(1), None, &Operations::PADDB, None).err();
+    assert!(matches!(error, Some(Error::MissingOperand(_))));
+
+    assert!(calculate(u64x1::new(1), None, &Operations::PINCB, None).is_ok());
+}
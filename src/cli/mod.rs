@@ -1,31 +1,485 @@
-use crate::types::*;
-use crate::calculations::*;
+use kalkulacka_hw::types::*;
+use kalkulacka_hw::calculations::*;
+use kalkulacka_hw::registers::*;
+use kalkulacka_hw::machine::*;
+use kalkulacka_hw::operations::operations;
+use kalkulacka_hw::custom::register_file;
+use kalkulacka_hw::program::{Program, Syntax, Trace};
+use kalkulacka_hw::encoding::{decode, encode, Decoded};
+use kalkulacka_hw::intrinsics::Expression;
+use kalkulacka_hw::codegen::Snippet;
+#[cfg(target_arch = "x86_64")]
+use kalkulacka_hw::verification::verify;
+use std::collections::HashMap;
 use structopt::StructOpt;
-use crate::utils::parse_hex;
-use strum::VariantNames;
+use structopt::clap::{self, ErrorKind};
+use kalkulacka_hw::error::Error;
+use kalkulacka_hw::utils::{parse_bytes, parse_hex, parse_mask, parse_number};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use strum::IntoEnumIterator;
 
 #[cfg_attr(feature = "debug", derive(Debug))]
 #[derive(StructOpt)]
-#[structopt(name = "Kalkulačka!", about = "Kalkulačka pro pár operací s 64bit registry MM1 a MM2.")]
+#[structopt(name = "Kalkulačka!", about = "Kalkulačka pro pár operací s 64bit registry MM0 až MM7.")]
 struct Opt {
+    ///Stav registru MM0
+    #[structopt(long, parse(try_from_str = parse_hex), default_value = "0")]
+    mm0: u64,
     ///Stav registru MM1
-    #[structopt(long, parse(try_from_str = parse_hex))]
+    #[structopt(long, parse(try_from_str = parse_hex), default_value = "0")]
     mm1: u64,
     ///Stav registru MM2
     #[structopt(long, parse(try_from_str = parse_hex), default_value = "0")]
     mm2: u64,
-    ///Operace, která se má provést s registry
-    #[structopt(short, long, possible_values = Operations::VARIANTS)]
-    operation: Operations,
+    ///Stav registru MM3
+    #[structopt(long, parse(try_from_str = parse_hex), default_value = "0")]
+    mm3: u64,
+    ///Stav registru MM4
+    #[structopt(long, parse(try_from_str = parse_hex), default_value = "0")]
+    mm4: u64,
+    ///Stav registru MM5
+    #[structopt(long, parse(try_from_str = parse_hex), default_value = "0")]
+    mm5: u64,
+    ///Stav registru MM6
+    #[structopt(long, parse(try_from_str = parse_hex), default_value = "0")]
+    mm6: u64,
+    ///Stav registru MM7
+    #[structopt(long, parse(try_from_str = parse_hex), default_value = "0")]
+    mm7: u64,
+    ///Stav 32bitového registru, např. EAX=10
+    #[structopt(long, parse(try_from_str = parse_general))]
+    gpr: Vec<(GeneralRegister, u32)>,
+    ///Obsah simulované paměti od adresy 0 v šestnáctkové soustavě, např. "01 02 FF"
+    #[structopt(long)]
+    memory: Option<Memory>,
+    ///Operace, která se má provést s registry (PADDB, ..., MOVD, MOVQ nebo vlastní z --operations)
+    #[structopt(short, long, required_unless_one = &["program", "decode", "intrinsic", "verify"])]
+    operation: Option<String>,
+    ///Soubor s vlastními operacemi, na řádku NÁZEV ŠÍŘKA = výraz, např. "PAVGB b = (a + b + 1) >> 1"
+    #[structopt(long, parse(from_os_str), number_of_values = 1)]
+    operations: Vec<PathBuf>,
+    ///Cílový operand, do kterého se zapíše výsledek (MM0 až MM7, EAX, [EAX+8], ...)
+    #[structopt(long, default_value = "MM1")]
+    dst: Operand,
+    ///Zdrojový operand
+    #[structopt(long, default_value = "MM2")]
+    src: Operand,
+    ///Maska pruhů v šestnáctkové soustavě, bit i povoluje zápis pruhu i
+    #[structopt(long, parse(try_from_str = parse_mask))]
+    mask: Option<u8>,
+    ///Maskované pruhy vynulovat místo zachování cílového registru
+    #[structopt(long, requires = "mask")]
+    zero_masking: bool,
+    ///Po operaci provést EMMS a uvolnit zásobník x87
+    #[structopt(long)]
+    emms: bool,
+    ///Soubor s programem v syntaxi Intel, instrukce oddělené novým řádkem nebo "/", komentáře za ";"
+    #[structopt(long, parse(from_os_str), conflicts_with = "operation")]
+    program: Option<PathBuf>,
+    ///Strojový kód instrukcí v šestnáctkové soustavě, např. "0F FC C1", který se dekóduje a provede
+    #[structopt(long, conflicts_with_all = &["operation", "program"])]
+    decode: Option<String>,
+    ///Výraz s intrinsic funkcemi jazyka C, např. "_mm_add_pi8(_mm_set1_pi8(3), a)"
+    #[structopt(long, conflicts_with_all = &["operation", "program", "decode"])]
+    intrinsic: Option<Expression>,
+    ///Pojmenovaný vstup výrazu v šestnáctkové soustavě, např. a=0102030405060708
+    #[structopt(long, parse(try_from_str = parse_input))]
+    input: Vec<(String, u64x1)>,
+    ///Porovnat emulované operace s instrukcemi SSE2 procesoru na náhodných vstupech
+    #[structopt(long, conflicts_with_all = &["operation", "program", "decode", "intrinsic"])]
+    verify: bool,
+    ///Počet náhodných vstupů pro --verify
+    #[structopt(long, default_value = "10000")]
+    samples: usize,
+    ///Semínko generátoru náhodných vstupů pro --verify
+    #[structopt(long, default_value = "1")]
+    seed: u64,
+    ///Místo výpisu registrů vygenerovat kód výpočtu v C s intrinsic funkcemi a v NASM
+    #[structopt(long, conflicts_with_all = &["program", "decode", "intrinsic", "encode"])]
+    code: bool,
+    ///Místo provedení vypsat strojový kód instrukce nebo programu
+    #[structopt(long, conflicts_with_all = &["decode", "trace"])]
+    encode: bool,
+    ///Kódovat instrukce v podobě SSE2 s prefixem 66 pro registry XMM
+    #[structopt(long, requires = "encode")]
+    xmm: bool,
+    ///Vypsat stav registrů po každé instrukci programu
+    #[structopt(long)]
+    trace: bool,
+    ///Syntaxe výpisu instrukcí, program lze zapsat v kterékoli z nich
+    #[structopt(long, possible_values = &["intel", "att"], default_value = "intel")]
+    syntax: Syntax,
 }
 
 pub fn run() {
-    let Opt { mm1, mm2, operation } = Opt::from_args();
-    let mm1 = u64x1::new(mm1);
-    let mm2 = u64x1::new(mm2);
+    let help = operations_help();
+    let matches = Opt::clap().after_help(help.as_str()).get_matches();
+    let Opt { mm0, mm1, mm2, mm3, mm4, mm5, mm6, mm7, gpr, memory, operation, operations, dst, src, mask, zero_masking, emms, program, decode, intrinsic, input, verify, samples, seed, code, encode, xmm, trace, syntax } = Opt::from_clap(&matches);
+
+    let mut machine = Machine::default();
+    for (register, value) in Register::iter().zip([mm0, mm1, mm2, mm3, mm4, mm5, mm6, mm7].iter()) {
+        machine.registers.set(register, u64x1::new(*value));
+    }
+    for (register, value) in gpr {
+        machine.set_general(register, value);
+    }
+    if let Some(memory) = memory {
+        machine.memory = memory;
+    }
+    // Registered before parsing the operation, so that it can be a custom one.
+    for path in operations {
+        register_file(&path).unwrap_or_else(|error| exit(error));
+    }
+
+    if verify {
+        return run_verify(samples, seed);
+    }
+    if let Some(expression) = intrinsic {
+        return run_intrinsic(&expression, input.into_iter().collect());
+    }
+
+    match (program, decode, operation) {
+        (Some(program), _, _) if encode => run_encode(load_program(&program).instructions(), xmm, syntax),
+        (Some(program), _, _) => run_program(machine, &load_program(&program), trace, syntax),
+        (None, Some(bytes), _) => run_decode(machine, &bytes, trace, syntax),
+        (None, None, Some(operation)) => {
+            let operation = Mnemonic::from_str(&operation).unwrap_or_else(|error| exit(error));
+            let mask = mask.map(|bits| Mask {
+                bits,
+                mode: if zero_masking { MaskMode::Zero } else { MaskMode::Merge },
+            });
+            let instruction = Instruction::new(operation, dst, Some(src), mask)
+                .unwrap_or_else(|error| exit(error));
+            if code {
+                run_code(machine, instruction);
+            } else if encode {
+                let instructions = if emms { vec![instruction, Instruction::Emms] } else { vec![instruction] };
+                run_encode(&instructions, xmm, syntax);
+            } else {
+                run_instruction(machine, instruction, emms, syntax);
+            }
+        }
+        (None, None, None) => exit(Error::MissingOperand("Zadejte operaci, program, strojový kód nebo výraz.".to_string())),
+    }
+}
+
+fn load_program(path: &Path) -> Program {
+    fs::read_to_string(path)
+        .map_err(|error| Error::InvalidArgument(format!("Nelze načíst soubor \"{}\": {}", path.display(), error)))
+        .and_then(|src| Program::from_str(&src))
+        .unwrap_or_else(|error| exit(error))
+}
+
+fn run_program(machine: Machine, program: &Program, trace: bool, syntax: Syntax) {
+    if !trace {
+        for instruction in program.instructions() {
+            println!("{}", instruction.format(syntax));
+        }
+    }
+    execute_program(machine, program, trace, syntax);
+}
+
+fn run_encode(instructions: &[Instruction], xmm: bool, syntax: Syntax) {
+    let mut encoded = Vec::new();
+    let mut offset = 0;
+    for instruction in instructions {
+        let bytes = encode(instruction, xmm)
+            .unwrap_or_else(|error| exit(error));
+        let length = bytes.len();
+        encoded.push(Decoded { offset, bytes, instruction: *instruction, xmm });
+        offset += length;
+    }
+
+    print_decoded(&encoded, syntax);
+}
+
+fn run_decode(machine: Machine, bytes: &str, trace: bool, syntax: Syntax) {
+    let decoded = parse_bytes(bytes)
+        .and_then(|bytes| decode(&bytes))
+        .unwrap_or_else(|error| exit(error));
+
+    print_decoded(&decoded, syntax);
+    if trace {
+        println!();
+    }
+
+    if let Some(instruction) = decoded.iter().find(|instruction| instruction.xmm) {
+        exit(Error::Unsupported(format!("Instrukci {} nelze provést, kalkulačka simuluje jen registry MMX.", instruction.format(syntax))));
+    }
+
+    let program = Program::from(decoded.into_iter().map(|instruction| instruction.instruction).collect::<Vec<_>>());
+    execute_program(machine, &program, trace, syntax);
+}
+
+fn execute_program(mut machine: Machine, program: &Program, trace: bool, syntax: Syntax) {
+    let before = machine.clone();
+    let result = if trace {
+        let trace = program.trace(&mut machine);
+        print_trace(&trace, syntax);
+        trace.error.map_or(Ok(()), Err)
+    } else {
+        program.run(&mut machine)
+    };
+    println!();
+    print_register_files(&before.registers, &machine.registers);
+    print_general(&before, &machine);
+    print_memory(&before, &machine);
+    print_fpu(&before.registers, &machine.registers);
+
+    if let Err(error) = result {
+        exit(error);
+    }
+}
+
+fn run_instruction(mut machine: Machine, instruction: Instruction, emms: bool, syntax: Syntax) {
+    let before = machine.clone();
+    let calculation = machine.execute(&instruction)
+        .unwrap_or_else(|error| exit(error));
+
+    println!("{}", instruction.format(syntax));
+    if emms {
+        machine.execute(&Instruction::Emms).ok();
+        println!("{}", Instruction::Emms.format(syntax));
+    }
+    println!();
+    print_register_files(&before.registers, &machine.registers);
+
+    if let (Some(calculation), Instruction::Operation { destination, source, .. }) = (calculation, instruction) {
+        match (calculation.lanes, source) {
+            (EitherLanes::TwoRegisters(x, y), Some(source)) => {
+                print_lanes(destination, machine.registers.get(destination), &x);
+                print_lanes(source, machine.registers.get(source), &y);
+            }
+            (EitherLanes::OneRegister(x), _) | (EitherLanes::TwoRegisters(x, _), None) => {
+                print_lanes(destination, machine.registers.get(destination), &x);
+            }
+        }
+    }
+
+    print_general(&before, &machine);
+    print_memory(&before, &machine);
+    print_fpu(&before.registers, &machine.registers);
+}
+
+fn run_code(mut machine: Machine, instruction: Instruction) {
+    let (operation, destination, source) = match instruction {
+        Instruction::Operation { operation, destination, source: Some(source), mask: None } => match operation.builtin() {
+            Some(operation) => (operation, destination, source),
+            None => exit(Error::Unsupported(format!("Vlastní operace {} nemá odpovídající kód.", operation))),
+        },
+        _ => exit(Error::Unsupported("Kód lze vygenerovat jen pro operaci se zdrojovým registrem MMX bez masky.".to_string())),
+    };
+
+    let (a, b) = (machine.registers.get(destination), machine.registers.get(source));
+    machine.execute(&instruction)
+        .unwrap_or_else(|error| exit(error));
+    let snippet = Snippet { operation, destination, source, a, b, expected: machine.registers.get(destination) };
+
+    let code = snippet.c()
+        .and_then(|c| snippet.nasm().map(|nasm| format!("{}\n{}", c, nasm)))
+        .unwrap_or_else(|error| exit(error));
+    print!("{}", code);
+}
+
+#[cfg(target_arch = "x86_64")]
+fn run_verify(samples: usize, seed: u64) {
+    let reports = verify(samples, seed);
+
+    println!("{:<9}{:<11}{:<18}{:<10}Neshod", "Operace", "Instrukce", "Intrinsic SSE2", "Vzorků");
+    for report in &reports {
+        if let Some(hardware) = report.hardware {
+            println!(
+                "{:<9}{:<11}{:<18}{:<10}{}",
+                report.operation.to_string(), hardware.mnemonic, hardware.sse2, report.samples, report.mismatches.len(),
+            );
+        }
+    }
 
-    match calculate(mm1, mm2, &operation) {
-        EitherRegisters::OneRegister(x) => println!("{}", x),
-        EitherRegisters::TwoRegisters(x, y) => println!("MM1: {}\nMM2: {}", x, y),
+    let unchecked = reports.iter()
+        .filter(|report| report.hardware.is_none())
+        .map(|report| report.operation.to_string())
+        .collect::<Vec<_>>();
+    if !unchecked.is_empty() {
+        println!("
+Neověřeno, procesor nemá odpovídající instrukci: {}", unchecked.join(", "));
     }
-}
\ No newline at end of file
+
+    for report in reports.iter().filter(|report| !report.mismatches.is_empty()) {
+        println!("\nNeshody operace {}", report.operation);
+        println!("{:<18}{:<18}{:<18}Procesor", "A", "B", "Kalkulačka");
+        let result = |result: &Result<u64x1, Error>| match result {
+            Ok(value) => value.to_string(),
+            Err(error) => format!("chyba: {}", error),
+        };
+        for mismatch in report.mismatches.iter().take(5) {
+            println!("{:<18}{:<18}{:<18}{}", mismatch.a.to_string(), mismatch.b.to_string(), result(&mismatch.emulated), result(&mismatch.hardware));
+        }
+    }
+
+    if reports.iter().any(|report| !report.mismatches.is_empty()) {
+        std::process::exit(1);
+    }
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn run_verify(_samples: usize, _seed: u64) {
+    exit(Error::Unsupported("Ověření proti procesoru je dostupné jen na x86_64.".to_string()));
+}
+
+fn run_intrinsic(expression: &Expression, inputs: HashMap<String, u64x1>) {
+    let value = expression.evaluate(&inputs)
+        .unwrap_or_else(|error| exit(error));
+
+    println!("{}", expression);
+    println!("= {}", value);
+}
+
+/// Lists every operation with its description and pseudocode below the options.
+fn operations_help() -> String {
+    operations().into_iter().fold("OPERACE:".to_string(), |help, operation| {
+        let metadata = operation.metadata();
+        format!("{}\n    {}: {}\n        {}\n        {}", help, operation, metadata, metadata.description, metadata.pseudocode)
+    })
+}
+
+/// Reports the error like clap does and exits, missing operands get clap's exit code for missing arguments.
+fn exit(error: Error) -> ! {
+    let kind = match error {
+        Error::MissingOperand(_) => ErrorKind::MissingRequiredArgument,
+        _ => ErrorKind::InvalidValue,
+    };
+    clap::Error::with_description(&error.to_string(), kind).exit()
+}
+
+fn parse_input(src: &str) -> Result<(String, u64x1), Error> {
+    let (name, value) = src.split_at(src.find('=').ok_or_else(|| Error::Parse(format!("Očekáváno JMÉNO=HODNOTA, ne \"{}\".", src)))?);
+    let value = value[1..].trim();
+    let value = value.strip_prefix("0x").or_else(|| value.strip_prefix("0X")).unwrap_or(value);
+    let value = parse_hex(value)?;
+    Ok((name.trim().to_string(), u64x1::new(value)))
+}
+
+fn parse_general(src: &str) -> Result<(GeneralRegister, u32), Error> {
+    let (register, value) = src.split_at(src.find('=').ok_or_else(|| Error::Parse(format!("Očekáváno REGISTR=HODNOTA, ne \"{}\".", src)))?);
+    let register = GeneralRegister::from_str(&register.trim().to_uppercase())
+        .map_err(|_| Error::Parse(format!("Neznámý registr \"{}\".", register)))?;
+    let value = parse_number(&value[1..])?;
+    Ok((register, value as u32))
+}
+
+/// Names the calculator's operation next to instructions with a different mnemonic, e.g. `PADDUSB` computing `PADDSB`.
+fn print_decoded(instructions: &[Decoded], syntax: Syntax) {
+    println!("{:<8}{:<24}{:<28}Operace", "Offset", "Bajty", "Instrukce");
+    for instruction in instructions {
+        let bytes = instruction.bytes.iter().map(|byte| format!("{:02X}", byte)).collect::<Vec<_>>().join(" ");
+        let text = instruction.format(syntax);
+        let operation = instruction.operation()
+            .map(|operation| operation.to_string())
+            .filter(|operation| !text.to_uppercase().starts_with(&format!("{} ", operation)))
+            .unwrap_or_default();
+        let line = format!("{:<8}{:<24}{:<28}{}", format!("{:04X}", instruction.offset), bytes, text, operation);
+        println!("{}", line.trim_end());
+    }
+}
+
+fn print_register_files(before: &RegisterFile, after: &RegisterFile) {
+    println!("{:<9}{:<18}Po", "Registr", "Před");
+    for ((register, x), (_, y)) in before.iter().zip(after.iter()) {
+        println!("{:<9}{:<18}{}{}", register.to_string(), x.to_string(), y, if x == y { "" } else { "  *" });
+    }
+}
+
+/// One row per step, columns only for registers changed by the program, `*` marks the changes made by the step.
+fn print_trace(trace: &Trace, syntax: Syntax) {
+    let snapshot = |machine: &Machine| {
+        Register::iter()
+            .map(|register| (register.to_string(), machine.registers.get(register).to_string()))
+            .chain(GeneralRegister::iter().map(|register| (register.to_string(), format!("{:08X}", machine.general(register)))))
+            .collect::<Vec<_>>()
+    };
+    let states = (0..=trace.steps.len()).filter_map(|step| trace.state(step)).collect::<Vec<_>>();
+    let snapshots = states.iter().map(|machine| snapshot(machine)).collect::<Vec<_>>();
+    let columns = (0..snapshots[0].len())
+        .filter(|column| snapshots.windows(2).any(|pair| pair[0][*column] != pair[1][*column]))
+        .collect::<Vec<_>>();
+    let memory_changed = states.windows(2).any(|pair| pair[0].memory.bytes()[..] != pair[1].memory.bytes()[..]);
+
+    let labels = std::iter::once("(počáteční stav)".to_string())
+        .chain(trace.steps.iter().map(|step| step.instruction.format(syntax)))
+        .collect::<Vec<_>>();
+    let width = labels.iter().map(|label| label.chars().count()).max().unwrap_or(0) + 2;
+
+    print!("{:<6}{:<2$}", "Krok", "Instrukce", width);
+    for column in &columns {
+        print!("{:<20}", snapshots[0][*column].0);
+    }
+    println!("{}", if memory_changed { "Paměť" } else { "" });
+
+    for (step, label) in labels.iter().enumerate() {
+        let previous = step.saturating_sub(1);
+        print!("{:<6}{:<2$}", step, label, width);
+        for column in &columns {
+            let (x, y) = (&snapshots[previous][*column].1, &snapshots[step][*column].1);
+            print!("{:<20}", format!("{}{}", y, if x == y { "" } else { " *" }));
+        }
+        let memory_written = states[previous].memory.bytes()[..] != states[step].memory.bytes()[..];
+        println!("{}", if memory_written { "*" } else { "" });
+    }
+
+    if let Some(error) = &trace.error {
+        println!("{:<6}{}", trace.steps.len() + 1, error);
+    }
+}
+
+fn print_general(before: &Machine, after: &Machine) {
+    if GeneralRegister::iter().all(|register| before.general(register) == 0 && after.general(register) == 0) {
+        return;
+    }
+
+    println!("\n{:<9}{:<18}Po", "Registr", "Před");
+    for register in GeneralRegister::iter() {
+        let (x, y) = (before.general(register), after.general(register));
+        println!("{:<9}{:<18}{:08X}{}", register.to_string(), format!("{:08X}", x), y, if x == y { "" } else { "  *" });
+    }
+}
+
+fn print_memory(before: &Machine, after: &Machine) {
+    let rows = before.memory.bytes().chunks(16)
+        .zip(after.memory.bytes().chunks(16))
+        .enumerate()
+        .filter(|(_, (x, y))| x.iter().chain(y.iter()).any(|byte| *byte != 0))
+        .collect::<Vec<_>>();
+    if rows.is_empty() {
+        return;
+    }
+
+    println!("\n{:<8}{:<50}Po", "Adresa", "Před");
+    for (row, (x, y)) in rows {
+        let format_row = |bytes: &[u8]| bytes.iter().map(|byte| format!("{:02X}", byte)).collect::<Vec<_>>().join(" ");
+        println!("{:<8}{:<50}{}{}", format!("{:04X}", row * 16), format_row(x), format_row(y), if x == y { "" } else { "  *" });
+    }
+}
+
+fn print_lanes(register: Register, value: u64x1, lanes: &LaneFlags) {
+    println!("\nPruhy registru {} ({} bitů)", register, lanes.bits());
+    println!("{:<6}{:<18}Stav", "Pruh", "Hodnota");
+    for (index, status) in lanes.iter().enumerate() {
+        let lane = format!("{:01$X}", value.lane(index, lanes.bits()), lanes.bits() / 4);
+        println!("{:<6}{:<18}{}", index, lane, status);
+    }
+}
+
+fn print_fpu(before: &RegisterFile, after: &RegisterFile) {
+    let fpu = after.fpu();
+    println!("\nStav x87: TOP = {}, tag word {:04X} → {:04X}", fpu.top(), before.fpu().tag_word(), fpu.tag_word());
+    println!("{:<9}{:<11}Exponent", "Registr", "Tag");
+    for register in Register::iter() {
+        println!("{:<9}{:<11}{:04X}", register.to_string(), fpu.tag(register).to_string(), fpu.exponent(register));
+    }
+
+    if let Some(register) = fpu.tainted_st(0) {
+        println!("\nVarování: po operaci chybí EMMS, následující instrukce x87 čtoucí ST(0) by místo čísla s plovoucí řádovou čárkou četla data registru {}.", register);
+    }
+}
@@ -0,0 +1,132 @@
+use crate::types::*;
+use crate::registers::Register;
+use crate::error::Error;
+
+/// Calculation `destination = operation(destination, source)` with its expected result.
+#[derive(Clone)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct Snippet {
+    pub operation: Operations,
+    pub destination: Register,
+    pub source: Register,
+    pub a: u64x1,
+    pub b: u64x1,
+    pub expected: u64x1,
+}
+
+impl Snippet {
+    /// Program asserting the result, `_mm_mul_su32` and `_mm_add_si64` need SSE2 and `emmintrin.h`.
+    pub fn c(&self) -> Result<String, Error> {
+        let hardware = self.operation.metadata().hardware.as_ref()
+            .ok_or_else(|| Error::Unsupported(format!("Operace {} nemá odpovídající intrinsic funkci.", self.operation)))?;
+        let intrinsic = hardware.intrinsics[0];
+        let header = hardware.header;
+        let set = |value: u64x1| format!("_mm_set_pi32((int)0x{:08X}u, (int)0x{:08X}u)", value.lane(1, 32), value.lane(0, 32));
+
+        Ok(format!(
+            "#include <{header}>
+#include <assert.h>
+#include <stdint.h>
+#include <string.h>
+
+int main(void) {{
+    __m64 a = {a}; /* {destination} */
+    __m64 b = {b}; /* {source} */
+    __m64 result = {intrinsic}(a, b);
+
+    uint64_t actual;
+    memcpy(&actual, &result, sizeof actual);
+    _mm_empty();
+
+    assert(actual == 0x{expected}ull);
+    return 0;
+}}
+",
+            header = header,
+            a = set(self.a),
+            b = set(self.b),
+            destination = self.destination,
+            source = self.source,
+            intrinsic = intrinsic,
+            expected = self.expected,
+        ))
+    }
+
+    /// 64-bit function `check` returning 1 in `EAX` when the result matches.
+    pub fn nasm(&self) -> Result<String, Error> {
+        let mnemonic = self.operation.metadata().hardware.as_ref()
+            .map(|hardware| hardware.mnemonic.to_lowercase())
+            .ok_or_else(|| Error::Unsupported(format!("Operace {} nemá odpovídající instrukci.", self.operation)))?;
+        let destination = self.destination.to_string().to_lowercase();
+        let source = self.source.to_string().to_lowercase();
+
+        Ok(format!(
+            "bits 64
+
+section .data
+    a:        dq 0x{a}
+    b:        dq 0x{b}
+    expected: dq 0x{expected}
+
+section .text
+global check
+check:
+    movq {destination}, [rel a]
+    movq {source}, [rel b]
+    {mnemonic} {destination}, {source}
+    movq rax, {destination}
+    emms
+    cmp rax, [rel expected]
+    sete al
+    movzx eax, al
+    ret
+",
+            a = self.a,
+            b = self.b,
+            expected = self.expected,
+            destination = destination,
+            source = source,
+            mnemonic = mnemonic,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snippet(operation: Operations) -> Snippet {
+        Snippet {
+            operation,
+            destination: Register::MM1,
+            source: Register::MM2,
+            a: u64x1::new(0x1122_3344_55FF_0001),
+            b: u64x1::new(0x0000_0000_0001_0001),
+            expected: u64x1::new(0x1122_3344_55FF_0102),
+        }
+    }
+
+    #[test]
+    fn c_uses_the_intrinsic_and_expected_value() {
+        let c = snippet(Operations::PADDSB).c().unwrap();
+        assert!(c.starts_with("#include <mmintrin.h>\n"));
+        assert!(c.contains("__m64 a = _mm_set_pi32((int)0x11223344u, (int)0x55FF0001u); /* MM1 */"));
+        assert!(c.contains("__m64 result = _mm_adds_pu8(a, b);"));
+        assert!(c.contains("assert(actual == 0x1122334455FF0102ull);"));
+        assert!(snippet(Operations::PMULLD).c().unwrap().starts_with("#include <emmintrin.h>\n"));
+    }
+
+    #[test]
+    fn nasm_uses_the_real_mnemonic() {
+        let nasm = snippet(Operations::PADDSB).nasm().unwrap();
+        assert!(nasm.contains("    paddusb mm1, mm2\n"));
+        assert!(nasm.contains("expected: dq 0x1122334455FF0102\n"));
+        assert!(snippet(Operations::PMULLD).nasm().unwrap().contains("pmuludq mm1, mm2"));
+    }
+
+    #[test]
+    fn operations_without_an_instruction_are_unsupported() {
+        assert!(matches!(snippet(Operations::PINCB).c(), Err(Error::Unsupported(_))));
+        assert!(matches!(snippet(Operations::PADDSD).nasm(), Err(Error::Unsupported(_))));
+    }
+}
@@ -0,0 +1,338 @@
+use std::borrow::Cow;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use crate::types::*;
+use crate::calculations::Lanes;
+use crate::operations::{register_all, Operation};
+use crate::error::Error;
+
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub enum UnaryOperator {
+    Negate,
+    Not,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub enum BinaryOperator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+    ShiftLeft,
+    ShiftRight,
+    And,
+    Or,
+    Xor,
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub enum Function {
+    Min,
+    Max,
+    /// Clamps to the unsigned range of the lane.
+    Saturate,
+    /// Clamps to the signed range of the lane.
+    SignedSaturate,
+    /// Reads the lane bits as a signed number.
+    SignExtend,
+}
+
+pub const FUNCTIONS: [(&str, Function); 5] = [
+    ("min", Function::Min),
+    ("max", Function::Max),
+    ("sat", Function::Saturate),
+    ("ssat", Function::SignedSaturate),
+    ("sx", Function::SignExtend),
+];
+
+impl Function {
+    pub fn from_name(name: &str) -> Option<Self> {
+        FUNCTIONS.iter().find(|(candidate, _)| candidate.eq_ignore_ascii_case(name)).map(|(_, function)| *function)
+    }
+
+    pub fn arguments(&self) -> usize {
+        match self {
+            Function::Min | Function::Max => 2,
+            Function::Saturate | Function::SignedSaturate | Function::SignExtend => 1,
+        }
+    }
+}
+
+/// Computation of one result lane from the lanes `a` of the destination and `b` of the source.
+/// Intermediate values are 128-bit signed, so e.g. `a * b` of 64-bit lanes doesn't overflow.
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub enum LaneExpression {
+    Number(i128),
+    A,
+    B,
+    Unary(UnaryOperator, Box<LaneExpression>),
+    Binary(BinaryOperator, Box<LaneExpression>, Box<LaneExpression>),
+    Call(Function, Vec<LaneExpression>),
+}
+
+struct Lane {
+    a: i128,
+    b: i128,
+    bits: usize,
+    status: LaneStatus,
+}
+
+impl LaneExpression {
+    fn any(&self, predicate: &dyn Fn(&LaneExpression) -> bool) -> bool {
+        predicate(self) || match self {
+            LaneExpression::Number(_) | LaneExpression::A | LaneExpression::B => false,
+            LaneExpression::Unary(_, operand) => operand.any(predicate),
+            LaneExpression::Binary(_, left, right) => left.any(predicate) || right.any(predicate),
+            LaneExpression::Call(_, arguments) => arguments.iter().any(|argument| argument.any(predicate)),
+        }
+    }
+
+    fn calls(&self, function: Function) -> bool {
+        self.any(&|expression| matches!(expression, LaneExpression::Call(called, _) if *called == function))
+    }
+
+    fn evaluate(&self, lane: &mut Lane) -> Result<i128, Error> {
+        let overflow = || Error::InvalidArgument("Mezivýsledek se nevejde do 128 bitů.".to_string());
+
+        match self {
+            LaneExpression::Number(value) => Ok(*value),
+            LaneExpression::A => Ok(lane.a),
+            LaneExpression::B => Ok(lane.b),
+            LaneExpression::Unary(operator, operand) => {
+                let operand = operand.evaluate(lane)?;
+                match operator {
+                    UnaryOperator::Negate => operand.checked_neg().ok_or_else(overflow),
+                    UnaryOperator::Not => Ok(!operand),
+                }
+            }
+            LaneExpression::Binary(operator, left, right) => {
+                let (left, right) = (left.evaluate(lane)?, right.evaluate(lane)?);
+                let shift = || match right {
+                    right if right < 0 => Err(Error::InvalidArgument(format!("Záporný posun o {}.", right))),
+                    right => Ok(right.min(128) as u32),
+                };
+
+                match operator {
+                    BinaryOperator::Add => left.checked_add(right).ok_or_else(overflow),
+                    BinaryOperator::Subtract => left.checked_sub(right).ok_or_else(overflow),
+                    BinaryOperator::Multiply => left.checked_mul(right).ok_or_else(overflow),
+                    BinaryOperator::Divide | BinaryOperator::Remainder if right == 0 => {
+                        Err(Error::InvalidArgument("Dělení nulou.".to_string()))
+                    }
+                    BinaryOperator::Divide => left.checked_div(right).ok_or_else(overflow),
+                    BinaryOperator::Remainder => left.checked_rem(right).ok_or_else(overflow),
+                    BinaryOperator::ShiftLeft => Ok(left.checked_shl(shift()?).unwrap_or(0)),
+                    BinaryOperator::ShiftRight => Ok(left.checked_shr(shift()?).unwrap_or(if left < 0 { -1 } else { 0 })),
+                    BinaryOperator::And => Ok(left & right),
+                    BinaryOperator::Or => Ok(left | right),
+                    BinaryOperator::Xor => Ok(left ^ right),
+                    BinaryOperator::Equal => Ok((left == right) as i128),
+                    BinaryOperator::NotEqual => Ok((left != right) as i128),
+                    BinaryOperator::Less => Ok((left < right) as i128),
+                    BinaryOperator::LessOrEqual => Ok((left <= right) as i128),
+                    BinaryOperator::Greater => Ok((left > right) as i128),
+                    BinaryOperator::GreaterOrEqual => Ok((left >= right) as i128),
+                }
+            }
+            LaneExpression::Call(function, arguments) => {
+                let arguments = arguments.iter().map(|argument| argument.evaluate(lane)).collect::<Result<Vec<_>, _>>()?;
+                let bits = lane.bits as u32;
+
+                Ok(match function {
+                    Function::Min => arguments[0].min(arguments[1]),
+                    Function::Max => arguments[0].max(arguments[1]),
+                    Function::Saturate => lane.saturate(arguments[0], 0, (1 << bits) - 1),
+                    Function::SignedSaturate => lane.saturate(arguments[0], -(1 << (bits - 1)), (1 << (bits - 1)) - 1),
+                    Function::SignExtend => {
+                        let value = arguments[0] & ((1 << bits) - 1);
+                        if value >> (bits - 1) == 1 { value - (1 << bits) } else { value }
+                    }
+                })
+            }
+        }
+    }
+}
+
+impl Lane {
+    fn saturate(&mut self, value: i128, min: i128, max: i128) -> i128 {
+        if value > max {
+            self.status = LaneStatus::SaturatedHigh;
+            max
+        } else if value < min {
+            self.status = LaneStatus::SaturatedLow;
+            min
+        } else {
+            value
+        }
+    }
+}
+
+/// Operation defined by a line like `PMULHRB b = sat((a * b + 128) >> 8)`.
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct CustomOperation {
+    name: String,
+    expression: LaneExpression,
+    metadata: Metadata,
+}
+
+impl CustomOperation {
+    /// `source` is the expression as written, it's shown as the pseudocode.
+    pub fn new(name: String, width: LaneWidth, expression: LaneExpression, source: &str) -> Self {
+        let saturating = expression.calls(Function::Saturate) || expression.calls(Function::SignedSaturate);
+        let signed = expression.calls(Function::SignedSaturate) || expression.calls(Function::SignExtend);
+        let metadata = Metadata {
+            kind: Kind::Expression,
+            width,
+            signedness: if signed { Signedness::Signed } else { Signedness::Unsigned },
+            overflow: if saturating { Overflow::Saturation } else { Overflow::Wraparound },
+            operands: if expression.any(&|expression| *expression == LaneExpression::B) { 2 } else { 1 },
+            shape: Shape::Lanes,
+            description: Cow::Owned(format!("Vlastní operace nad {}bitovými pruhy.", width.bits())),
+            pseudocode: Cow::Owned(format!("mm1[i] = {}", source.trim())),
+            hardware: None,
+            homonym: None,
+        };
+
+        Self { name, expression, metadata }
+    }
+}
+
+impl Operation for CustomOperation {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// Lanes that don't fit the width and signedness are truncated and marked as overflowed, e.g. `a - b` below zero.
+    fn evaluate(&self, mm1: u64x1, mm2: u64x1) -> Result<(Lanes, Option<Lanes>), Error> {
+        let bits = self.metadata.width.bits();
+        let mut result = u64x1::default();
+        let mut statuses = [LaneStatus::Unchanged; 8];
+
+        for (index, status) in statuses.iter_mut().enumerate().take(self.metadata.width.lanes()) {
+            let mut lane = Lane {
+                a: mm1.lane(index, bits) as i128,
+                b: mm2.lane(index, bits) as i128,
+                bits,
+                status: LaneStatus::Unchanged,
+            };
+            let value = self.expression.evaluate(&mut lane)?;
+            let range = match self.metadata.signedness {
+                Signedness::Signed => -(1 << (bits - 1))..1 << (bits - 1),
+                Signedness::Unsigned => 0..1 << bits,
+            };
+            if lane.status == LaneStatus::Unchanged && !range.contains(&value) {
+                lane.status = LaneStatus::Overflowed;
+            }
+
+            result = result.with_lane(index, bits, value as u64);
+            *status = lane.status;
+        }
+
+        Ok(((result, LaneFlags::new(bits, &statuses)), None))
+    }
+}
+
+/// Parses definitions, one per line, `;` and `#` start comments.
+pub fn parse_operations(src: &str) -> Result<Vec<CustomOperation>, Error> {
+    src.lines()
+        .enumerate()
+        .map(|(number, line)| (number, line.find(&[';', '#'][..]).map_or(line, |index| &line[..index]).trim()))
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(number, line)| CustomOperation::from_str(line).map_err(|error| error.context(format!("Řádek {}", number + 1))))
+        .collect()
+}
+
+/// Registers every operation defined in the file, nothing is registered when one of them is invalid.
+pub fn register_file(path: &Path) -> Result<Vec<&'static dyn Operation>, Error> {
+    let src = fs::read_to_string(path)
+        .map_err(|error| Error::InvalidArgument(format!("Nelze načíst soubor \"{}\": {}", path.display(), error)))?;
+
+    register_operations(parse_operations(&src)?)
+}
+
+/// Nothing is registered when one of the names clashes.
+fn register_operations(operations: Vec<CustomOperation>) -> Result<Vec<&'static dyn Operation>, Error> {
+    register_all(operations.into_iter().map(|operation| Box::new(operation) as Box<dyn Operation>).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operations::find;
+
+    fn evaluate(definition: &str, a: u64, b: u64) -> (u64, LaneFlags) {
+        let operation = CustomOperation::from_str(definition).unwrap();
+        let ((result, lanes), _) = operation.evaluate(u64x1::new(a), u64x1::new(b)).unwrap();
+        (result.lane(0, 64), lanes)
+    }
+
+    #[test]
+    fn rounded_high_product_saturates() {
+        let (result, lanes) = evaluate("PMULHRB b = sat((a * b + 128) >> 8)", 0x0000_0000_0000_FF10, 0x0000_0000_0000_FF10);
+
+        assert_eq!(result, 0x0000_0000_0000_FE01);
+        assert!(lanes.iter().all(|status| status == LaneStatus::Unchanged));
+    }
+
+    #[test]
+    fn out_of_range_lanes_are_flagged() {
+        let (result, lanes) = evaluate("PSUBB b = a - b", 0x0000_0000_0000_0001, 0x0000_0000_0000_0002);
+        assert_eq!(result, 0x0000_0000_0000_00FF);
+        assert!(lanes.status(0) == LaneStatus::Overflowed);
+
+        let (result, lanes) = evaluate("PSUBXB b = sx(a) - sx(b)", 0x0000_0000_0000_0001, 0x0000_0000_0000_0002);
+        assert_eq!(result, 0x0000_0000_0000_00FF);
+        assert!(lanes.status(0) == LaneStatus::Unchanged);
+
+        let (_, lanes) = evaluate("PADDXB b = sx(a) + sx(b)", 0x0000_0000_0000_007F, 0x0000_0000_0000_0001);
+        assert!(lanes.status(0) == LaneStatus::Overflowed);
+
+        let (result, lanes) = evaluate("PADDSSW w = ssat(sx(a) + sx(b))", 0x0000_0000_0000_7FFF, 0x0000_0000_0000_0001);
+        assert_eq!(result, 0x0000_0000_0000_7FFF);
+        assert!(lanes.status(0) == LaneStatus::SaturatedHigh);
+
+        let (_, lanes) = evaluate("PSHLB b = a << 1", 0x0000_0000_0000_0080, 0);
+        assert!(lanes.status(0) == LaneStatus::Overflowed);
+    }
+
+    #[test]
+    fn metadata_follows_expression() {
+        let operation = CustomOperation::from_str("PNEGD d = -sx(a)").unwrap();
+
+        assert!(!operation.requires_mm2());
+        assert!(operation.metadata().signedness == Signedness::Signed);
+        assert!(parse_operations("PX b = min(a)\n").is_err());
+        assert!(parse_operations("; comment\nPAVGB b = (a + b + 1) >> 1 # rounded\n").is_ok());
+    }
+
+    #[test]
+    fn clash_on_last_line_registers_nothing() {
+        let builtin = parse_operations("PABSCLASHB b = a\nPADDB b = a + b\n").unwrap();
+        assert!(register_operations(builtin).is_err());
+        assert!(find("PABSCLASHB").is_none());
+
+        let duplicate = parse_operations("PDUPCLASHW w = a\npdupclashw w = b\n").unwrap();
+        assert!(register_operations(duplicate).is_err());
+        assert!(find("PDUPCLASHW").is_none());
+
+        let valid = parse_operations("PNEWCLASHD d = a ^ b\n").unwrap();
+        assert!(register_operations(valid).is_ok());
+        assert!(find("PNEWCLASHD").is_some());
+    }
+}
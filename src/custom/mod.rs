@@ -0,0 +1,5 @@
+#[allow(clippy::module_inception)]
+mod custom;
+mod parser;
+
+pub use custom::*;
@@ -0,0 +1,169 @@
+use std::str::FromStr;
+use crate::types::LaneWidth;
+use crate::custom::*;
+use crate::utils::parse_number;
+use crate::error::Error;
+
+/// From the loosest binding level, comparisons, to the tightest, multiplication.
+const LEVELS: [&[(&str, BinaryOperator)]; 7] = [
+    &[
+        ("==", BinaryOperator::Equal), ("!=", BinaryOperator::NotEqual),
+        ("<=", BinaryOperator::LessOrEqual), (">=", BinaryOperator::GreaterOrEqual),
+        ("<", BinaryOperator::Less), (">", BinaryOperator::Greater),
+    ],
+    &[("|", BinaryOperator::Or)],
+    &[("^", BinaryOperator::Xor)],
+    &[("&", BinaryOperator::And)],
+    &[("<<", BinaryOperator::ShiftLeft), (">>", BinaryOperator::ShiftRight)],
+    &[("+", BinaryOperator::Add), ("-", BinaryOperator::Subtract)],
+    &[("*", BinaryOperator::Multiply), ("/", BinaryOperator::Divide), ("%", BinaryOperator::Remainder)],
+];
+
+const OPERATORS: [&str; 8] = ["<<", ">>", "<=", ">=", "==", "!=", "+-*/%&|^~<>", "(),"];
+
+/// Parses a definition `NAME WIDTH = expression`, the width is `b`, `w`, `d`, `q` or the number of bits.
+impl FromStr for CustomOperation {
+    type Err = Error;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        let (head, expression) = src.split_at(src.find('=')
+            .ok_or_else(|| Error::Parse(format!("Očekáváno NÁZEV ŠÍŘKA = výraz, ne \"{}\".", src.trim())))?);
+        let expression = &expression[1..];
+
+        let mut head = head.split_whitespace();
+        let (name, width) = match (head.next(), head.next(), head.next()) {
+            (Some(name), Some(width), None) => (name, width),
+            _ => return Err(Error::Parse(format!("Očekáváno NÁZEV ŠÍŘKA před \"=\", ne \"{}\".", src.trim()))),
+        };
+        let width = match width.to_lowercase().as_str() {
+            "b" | "8" => LaneWidth::Byte,
+            "w" | "16" => LaneWidth::Word,
+            "d" | "32" => LaneWidth::Doubleword,
+            "q" | "64" => LaneWidth::Quadword,
+            _ => return Err(Error::Parse(format!("Neznámá šířka pruhu \"{}\", očekáváno b, w, d nebo q.", width))),
+        };
+
+        Ok(CustomOperation::new(name.to_uppercase(), width, expression.parse()?, expression))
+    }
+}
+
+/// Parses e.g. `sat((a * b + 128) >> 8)`, comparisons give 1 or 0.
+impl FromStr for LaneExpression {
+    type Err = Error;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser { tokens: tokenize(src)?, position: 0 };
+        let expression = parser.binary(0)?;
+        match parser.next() {
+            None => Ok(expression),
+            Some(token) => Err(Error::Parse(format!("Neočekávaný text \"{}\" za koncem výrazu.", token))),
+        }
+    }
+}
+
+fn tokenize(src: &str) -> Result<Vec<String>, Error> {
+    let mut tokens = Vec::new();
+    let mut rest = src.trim_start();
+
+    while let Some(c) = rest.chars().next() {
+        let length = if c.is_ascii_alphanumeric() || c == '_' {
+            rest.find(|c: char| !c.is_ascii_alphanumeric() && c != '_').unwrap_or(rest.len())
+        } else if OPERATORS[..6].iter().any(|operator| rest.starts_with(operator)) {
+            2
+        } else if OPERATORS[6..].iter().any(|operators| operators.contains(c)) {
+            1
+        } else {
+            return Err(Error::Parse(format!("Neočekávaný znak \"{}\".", c)));
+        };
+
+        tokens.push(rest[..length].to_string());
+        rest = rest[length..].trim_start();
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<String>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.position).map(String::as_str)
+    }
+
+    fn next(&mut self) -> Option<&str> {
+        let token = self.tokens.get(self.position)?;
+        self.position += 1;
+        Some(token)
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<(), Error> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => Err(Error::Parse(format!("Očekáváno \"{}\", ne \"{}\".", expected, token))),
+            None => Err(Error::Parse(format!("Očekáváno \"{}\", výraz končí.", expected))),
+        }
+    }
+
+    /// Left-associative operators of `LEVELS[level]` over operands binding tighter.
+    fn binary(&mut self, level: usize) -> Result<LaneExpression, Error> {
+        if level == LEVELS.len() {
+            return self.unary();
+        }
+
+        let mut left = self.binary(level + 1)?;
+        while let Some(operator) = self.peek().and_then(|token| LEVELS[level].iter().find(|(symbol, _)| *symbol == token)) {
+            let operator = operator.1;
+            self.position += 1;
+            let right = self.binary(level + 1)?;
+            left = LaneExpression::Binary(operator, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<LaneExpression, Error> {
+        let operator = match self.peek() {
+            Some("-") => UnaryOperator::Negate,
+            Some("~") => UnaryOperator::Not,
+            _ => return self.primary(),
+        };
+        self.position += 1;
+        Ok(LaneExpression::Unary(operator, Box::new(self.unary()?)))
+    }
+
+    fn primary(&mut self) -> Result<LaneExpression, Error> {
+        let token = self.next().ok_or_else(|| Error::Parse("Chybí výraz.".to_string()))?.to_string();
+
+        if token == "(" {
+            let expression = self.binary(0)?;
+            self.expect(")")?;
+            return Ok(expression);
+        }
+        if token.starts_with(|c: char| c.is_ascii_digit()) {
+            let value = parse_number(&token).map_err(|_| Error::Parse(format!("Neplatné číslo \"{}\".", token)))?;
+            return Ok(LaneExpression::Number(value as i128));
+        }
+        if token.eq_ignore_ascii_case("a") {
+            return Ok(LaneExpression::A);
+        }
+        if token.eq_ignore_ascii_case("b") {
+            return Ok(LaneExpression::B);
+        }
+
+        let function = Function::from_name(&token).ok_or_else(|| Error::Parse(format!("Neznámý symbol \"{}\", očekáváno a, b, číslo nebo funkce.", token)))?;
+        self.expect("(")?;
+        let mut arguments = vec![self.binary(0)?];
+        while self.peek() == Some(",") {
+            self.position += 1;
+            arguments.push(self.binary(0)?);
+        }
+        self.expect(")")?;
+
+        if arguments.len() != function.arguments() {
+            return Err(Error::InvalidArgument(format!("Funkce {} očekává počet argumentů {}, zadáno {}.", token, function.arguments(), arguments.len())));
+        }
+        Ok(LaneExpression::Call(function, arguments))
+    }
+}
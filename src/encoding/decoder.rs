@@ -0,0 +1,299 @@
+use crate::operations::{operations, Operation};
+use crate::registers::Register;
+use crate::machine::*;
+use crate::program::Syntax;
+use crate::error::Error;
+
+pub const OPERAND_SIZE_PREFIX: u8 = 0x66;
+pub const ESCAPE: u8 = 0x0F;
+pub const EMMS: u8 = 0x77;
+/// `0F 38` starts the three-byte opcodes, e.g. the SSE4.1 `PMULLD`.
+pub const THREE_BYTE_ESCAPE: u8 = 0x38;
+
+/// `MOVD mm, r/m32`, `MOVD r/m32, mm`, `MOVQ mm, mm/m64` and `MOVQ mm/m64, mm`.
+pub const MOVD_LOAD: u8 = 0x6E;
+pub const MOVD_STORE: u8 = 0x7E;
+pub const MOVQ_LOAD: u8 = 0x6F;
+pub const MOVQ_STORE: u8 = 0x7F;
+
+/// Registers in the order of their ModRM encoding.
+pub const MMX_REGISTERS: [Register; 8] = [
+    Register::MM0, Register::MM1, Register::MM2, Register::MM3,
+    Register::MM4, Register::MM5, Register::MM6, Register::MM7,
+];
+pub const GENERAL_REGISTERS: [GeneralRegister; 8] = [
+    GeneralRegister::EAX, GeneralRegister::ECX, GeneralRegister::EDX, GeneralRegister::EBX,
+    GeneralRegister::ESP, GeneralRegister::EBP, GeneralRegister::ESI, GeneralRegister::EDI,
+];
+
+#[derive(Clone)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct Decoded {
+    pub offset: usize,
+    pub bytes: Vec<u8>,
+    pub instruction: Instruction,
+    /// SSE2 form on XMM registers, the calculator can only show it, not execute it.
+    pub xmm: bool,
+}
+
+impl Decoded {
+    /// Prints the real x86 mnemonic, e.g. `PADDUSB` for the calculator's `PADDSB`, see `operation`.
+    pub fn format(&self, syntax: Syntax) -> String {
+        let (mnemonic, destination, source) = match self.instruction {
+            Instruction::Operation { operation, destination, source, .. } => {
+                (mnemonic(operation), Operand::Mmx(destination), source.map(Operand::Mmx))
+            }
+            Instruction::Movd { destination, source } => ("MOVD", destination, Some(source)),
+            Instruction::Movq { destination, source } => ("MOVQ", destination, Some(source)),
+            Instruction::Emms => return self.instruction.format(syntax),
+        };
+
+        let destination = format_operand(&destination, self.xmm, syntax);
+        match (syntax, source) {
+            (Syntax::Intel, Some(source)) => format!("{} {}, {}", mnemonic, destination, format_operand(&source, self.xmm, syntax)),
+            (Syntax::Att, Some(source)) => format!("{} {}, {}", mnemonic.to_lowercase(), format_operand(&source, self.xmm, syntax), destination),
+            (Syntax::Intel, None) => format!("{} {}", mnemonic, destination),
+            (Syntax::Att, None) => format!("{} {}", mnemonic.to_lowercase(), destination),
+        }
+    }
+
+    /// The calculator's operation computed by the instruction.
+    pub fn operation(&self) -> Option<&'static dyn Operation> {
+        match self.instruction {
+            Instruction::Operation { operation, .. } => Some(operation),
+            _ => None,
+        }
+    }
+
+}
+
+fn mnemonic(operation: &dyn Operation) -> &str {
+    operation.metadata().hardware.as_ref().map_or_else(|| operation.name(), |hardware| hardware.mnemonic)
+}
+
+/// In the SSE2 form the ModRM byte names the XMM register with the same number.
+fn format_operand(operand: &Operand, xmm: bool, syntax: Syntax) -> String {
+    match (syntax, operand) {
+        (Syntax::Intel, Operand::Mmx(register)) if xmm => format!("XMM{}", *register as u8),
+        (Syntax::Att, Operand::Mmx(register)) if xmm => format!("%xmm{}", *register as u8),
+        (_, operand) => operand.format(syntax),
+    }
+}
+
+/// Decodes a sequence of instructions, e.g. `0F FC C1` is `PADDB MM0, MM1`.
+pub fn decode(bytes: &[u8]) -> Result<Vec<Decoded>, Error> {
+    let mut decoded = Vec::new();
+    let mut offset = 0;
+
+    while offset < bytes.len() {
+        let mut reader = Reader { bytes: &bytes[offset..], position: 0 };
+        let (instruction, xmm) = decode_instruction(&mut reader)
+            .map_err(|error| error.context(format!("Offset {:#X}", offset)))?;
+        decoded.push(Decoded {
+            offset,
+            bytes: bytes[offset..offset + reader.position].to_vec(),
+            instruction,
+            xmm,
+        });
+        offset += reader.position;
+    }
+
+    Ok(decoded)
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl Reader<'_> {
+    fn byte(&mut self) -> Result<u8, Error> {
+        let byte = *self.bytes.get(self.position).ok_or_else(|| Error::Parse("Neúplná instrukce.".to_string()))?;
+        self.position += 1;
+        Ok(byte)
+    }
+
+    fn u32(&mut self) -> Result<u32, Error> {
+        let mut bytes = [0; 4];
+        for byte in bytes.iter_mut() {
+            *byte = self.byte()?;
+        }
+        Ok(u32::from_le_bytes(bytes))
+    }
+}
+
+enum RegisterOrMemory {
+    Register(usize),
+    Memory(Address),
+}
+
+/// The operation named like the instruction with the opcode, which computes something else.
+fn homonym(code: &[u8]) -> Option<&'static dyn Operation> {
+    operations().into_iter().find(|operation| operation.metadata().homonym.as_ref().is_some_and(|homonym| homonym.opcode == code))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02X}", byte)).collect::<Vec<_>>().join(" ")
+}
+
+/// The operation computed by the instruction with the opcode following `0F`.
+fn operation(opcode: u8) -> Option<&'static dyn Operation> {
+    operations().into_iter().find(|operation| operation.metadata().hardware.as_ref().map(|hardware| hardware.opcode) == Some(opcode))
+}
+
+fn decode_instruction(reader: &mut Reader) -> Result<(Instruction, bool), Error> {
+    let mut byte = reader.byte()?;
+    let xmm = byte == OPERAND_SIZE_PREFIX;
+    if xmm {
+        byte = reader.byte()?;
+    }
+    if byte != ESCAPE {
+        return Err(Error::Unsupported(format!("Neznámý opcode {:02X}, instrukce MMX začínají bajtem 0F.", byte)));
+    }
+
+    let opcode = reader.byte()?;
+    if opcode == EMMS {
+        return if xmm {
+            Err(Error::Unsupported("Instrukce EMMS nemá tvar s prefixem 66.".to_string()))
+        } else {
+            Ok((Instruction::Emms, false))
+        };
+    }
+
+    let mut code = vec![ESCAPE, opcode];
+    if opcode == THREE_BYTE_ESCAPE {
+        code.push(reader.byte()?);
+    }
+    if let Some(operation) = homonym(&code) {
+        let metadata = operation.metadata();
+        let equivalent = metadata.hardware.as_ref()
+            .map_or(String::new(), |hardware| format!(" Operaci {} odpovídá instrukce {}.", operation, hardware.mnemonic));
+        return Err(Error::Unsupported(format!(
+            "Instrukce {} {}, operace kalkulačky stejného jména počítá jinak: {}{}",
+            operation, metadata.homonym.as_ref().map_or("", |homonym| homonym.description), metadata.description, equivalent,
+        )));
+    }
+    if code.len() > 2 {
+        return Err(Error::Unsupported(format!("Nepodporovaný opcode {}{}.", if xmm { "66 " } else { "" }, hex(&code))));
+    }
+
+    let (register, rm) = decode_modrm(reader)?;
+    let register = Operand::Mmx(MMX_REGISTERS[register]);
+    let (rm_mmx, rm_general) = match rm {
+        RegisterOrMemory::Register(index) => (Operand::Mmx(MMX_REGISTERS[index]), Operand::General(GENERAL_REGISTERS[index])),
+        RegisterOrMemory::Memory(address) => (Operand::Memory(address), Operand::Memory(address)),
+    };
+
+    let instruction = match opcode {
+        MOVD_LOAD => Instruction::new(Mnemonic::Movd, register, Some(rm_general), None),
+        MOVD_STORE => Instruction::new(Mnemonic::Movd, rm_general, Some(register), None),
+        MOVQ_LOAD if !xmm => Instruction::new(Mnemonic::Movq, register, Some(rm_mmx), None),
+        MOVQ_STORE if !xmm => Instruction::new(Mnemonic::Movq, rm_mmx, Some(register), None),
+        opcode => match operation(opcode) {
+            Some(operation) if matches!(rm_mmx, Operand::Memory(_)) => Err(Error::Unsupported(format!(
+                "{} {}, {} čte zdroj z paměti, kalkulačka počítá jen s registry MMX. Načtěte ho nejdřív instrukcí MOVQ.",
+                mnemonic(operation), format_operand(&register, xmm, Syntax::Intel), rm_mmx,
+            ))),
+            Some(operation) => Instruction::new(Mnemonic::Operation(operation), register, Some(rm_mmx), None),
+            None => Err(Error::Unsupported(format!("Nepodporovaný opcode {}0F {:02X}.", if xmm { "66 " } else { "" }, opcode))),
+        },
+    }?;
+
+    Ok((instruction, xmm))
+}
+
+/// 32-bit addressing, the SIB byte is supported only without an index register.
+fn decode_modrm(reader: &mut Reader) -> Result<(usize, RegisterOrMemory), Error> {
+    let modrm = reader.byte()?;
+    let (mode, register, rm) = (modrm >> 6, (modrm >> 3 & 0b111) as usize, (modrm & 0b111) as usize);
+    if mode == 0b11 {
+        return Ok((register, RegisterOrMemory::Register(rm)));
+    }
+
+    let base = if rm == 0b100 {
+        let sib = reader.byte()?;
+        if sib >> 3 & 0b111 != 0b100 {
+            return Err(Error::Unsupported("Adresování s indexovým registrem není podporováno.".to_string()));
+        }
+        (sib & 0b111) as usize
+    } else {
+        rm
+    };
+
+    let (base, displacement) = match (mode, base) {
+        (0b00, 0b101) => (None, reader.u32()?),
+        (0b00, base) => (Some(base), 0),
+        (0b01, base) => (Some(base), reader.byte()? as i8 as u32),
+        (_, base) => (Some(base), reader.u32()?),
+    };
+
+    Ok((register, RegisterOrMemory::Memory(Address {
+        base: base.map(|base| GENERAL_REGISTERS[base]),
+        displacement,
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::parse_bytes;
+
+    fn decode_str(src: &str) -> Result<Vec<String>, Error> {
+        Ok(decode(&parse_bytes(src)?)?.iter().map(|decoded| decoded.format(Syntax::Intel)).collect())
+    }
+
+    #[test]
+    fn decodes_instructions() {
+        assert_eq!(
+            decode_str("0F FC C1 0F DD D3 0F 77 0F 6E 03 0F 7E CA 0F 6F 45 08 0F 7F 4C 24 08 0F 7F 05 20 00 00 00").unwrap(),
+            [
+                "PADDB MM0, MM1", "PADDUSW MM2, MM3", "EMMS", "MOVD MM0, [EBX]", "MOVD EDX, MM1",
+                "MOVQ MM0, [EBP+0x8]", "MOVQ [ESP+0x8], MM1", "MOVQ [0x20], MM0",
+            ]
+        );
+        assert_eq!(decode_str("66 0F FE C1").unwrap(), ["PADDD XMM0, XMM1"]);
+        assert_eq!(decode_str("0F 6F 40 FC").unwrap(), ["MOVQ MM0, [EAX-0x4]"]);
+    }
+
+    #[test]
+    fn prints_the_real_mnemonic() {
+        // objdump -d of the instructions assembled by `as --32`.
+        for (bytes, att, operation) in [
+            ("0F DC CA", "paddusb %mm2, %mm1", "PADDSB"),
+            ("0F DD CA", "paddusw %mm2, %mm1", "PADDSW"),
+            ("0F F4 FE", "pmuludq %mm6, %mm7", "PMULLD"),
+            ("0F D4 C1", "paddq %mm1, %mm0", "PADDQ"),
+            ("66 0F DC CA", "paddusb %xmm2, %xmm1", "PADDSB"),
+            ("66 0F F4 CA", "pmuludq %xmm2, %xmm1", "PMULLD"),
+            ("66 0F 7E C8", "movd %xmm1, %eax", ""),
+        ].iter() {
+            let decoded = decode(&parse_bytes(bytes).unwrap()).unwrap();
+            assert_eq!(decoded[0].format(Syntax::Att), *att);
+            assert_eq!(decoded[0].operation().map_or(String::new(), |operation| operation.to_string()), *operation);
+        }
+        assert_eq!(decode_str("66 0F F4 CA").unwrap(), ["PMULUDQ XMM1, XMM2"]);
+    }
+
+    #[test]
+    fn homonyms_are_explained() {
+        for (bytes, mnemonic) in [("0F EC C1", "PADDSB"), ("0F ED C1", "PADDSW"), ("0F D5 C1", "PMULLW"), ("66 0F 38 40 CA", "PMULLD")].iter() {
+            let error = decode_str(bytes).unwrap_err();
+            assert!(matches!(&error, Error::Unsupported(message) if message.contains(&format!("Instrukce {} ", mnemonic))), "{}", error);
+        }
+        assert!(matches!(decode_str("66 0F 38 00 CA"), Err(Error::Unsupported(message)) if message.contains("66 0F 38 00")));
+    }
+
+    #[test]
+    fn memory_source_of_an_operation_is_unsupported() {
+        let error = decode_str("0F FC 00").unwrap_err();
+        assert!(matches!(&error, Error::Unsupported(message) if message.contains("PADDB MM0, [EAX]")), "{}", error);
+    }
+
+    #[test]
+    fn errors_name_the_offset() {
+        assert!(matches!(decode_str("0F FC C1 0F"), Err(Error::Parse(message)) if message.starts_with("Offset 0x3")));
+        assert!(matches!(decode_str("0F 77 90"), Err(Error::Unsupported(message)) if message.starts_with("Offset 0x2")));
+        assert!(matches!(decode_str("0F FC 04 88"), Err(Error::Unsupported(_))));
+        assert!(decode_str("66 0F 77").is_err());
+    }
+}
@@ -0,0 +1,152 @@
+use crate::machine::*;
+use crate::encoding::*;
+use crate::error::Error;
+
+/// Encodes the instruction, `xmm` selects the `66` prefixed SSE2 form on XMM registers.
+/// Operations become their equivalent instruction from the metadata, e.g. `PADDSB` is `PADDUSB`.
+pub fn encode(instruction: &Instruction, xmm: bool) -> Result<Vec<u8>, Error> {
+    let mut bytes = if xmm { vec![OPERAND_SIZE_PREFIX, ESCAPE] } else { vec![ESCAPE] };
+
+    match instruction {
+        Instruction::Operation { mask: Some(_), .. } => {
+            return Err(Error::Unsupported(format!("Instrukci {} nelze zakódovat, MMX nepodporuje masky.", instruction)));
+        }
+        Instruction::Operation { operation, destination, source, .. } => {
+            let opcode = operation.metadata().hardware.as_ref()
+                .map(|hardware| hardware.opcode)
+                .ok_or_else(|| Error::Unsupported(format!("Operace {} nemá strojový kód.", operation)))?;
+            let source = source.ok_or_else(|| Error::MissingOperand(format!("Instrukce {} vyžaduje zdrojový registr.", instruction)))?;
+
+            bytes.push(opcode);
+            bytes.push(modrm(0b11, *destination as u8, source as u8));
+        }
+        Instruction::Movd { destination: Operand::Mmx(register), source } => {
+            bytes.push(MOVD_LOAD);
+            encode_operand(&mut bytes, *register as u8, source);
+        }
+        Instruction::Movd { destination, source: Operand::Mmx(register) } => {
+            bytes.push(MOVD_STORE);
+            encode_operand(&mut bytes, *register as u8, destination);
+        }
+        Instruction::Movq { .. } if xmm => {
+            return Err(Error::Unsupported("Instrukce MOVQ nemá tvar s prefixem 66, registry XMM přesouvá MOVDQA.".to_string()));
+        }
+        Instruction::Movq { destination: Operand::Mmx(register), source } => {
+            bytes.push(MOVQ_LOAD);
+            encode_operand(&mut bytes, *register as u8, source);
+        }
+        Instruction::Movq { destination, source: Operand::Mmx(register) } => {
+            bytes.push(MOVQ_STORE);
+            encode_operand(&mut bytes, *register as u8, destination);
+        }
+        Instruction::Movd { .. } | Instruction::Movq { .. } => {
+            return Err(Error::Unsupported(format!("Instrukci {} nelze zakódovat, jeden z operandů musí být registr MMX.", instruction)));
+        }
+        Instruction::Emms if xmm => return Err(Error::Unsupported("Instrukce EMMS nemá tvar s prefixem 66.".to_string())),
+        Instruction::Emms => bytes.push(EMMS),
+    }
+
+    Ok(bytes)
+}
+
+fn modrm(mode: u8, register: u8, rm: u8) -> u8 {
+    mode << 6 | register << 3 | rm
+}
+
+fn encode_operand(bytes: &mut Vec<u8>, register: u8, operand: &Operand) {
+    match operand {
+        Operand::Mmx(rm) => bytes.push(modrm(0b11, register, *rm as u8)),
+        Operand::General(rm) => bytes.push(modrm(0b11, register, *rm as u8)),
+        Operand::Memory(address) => encode_address(bytes, register, address),
+    }
+}
+
+/// 32-bit addressing, `ESP` as a base needs the SIB byte and `EBP` always has a displacement.
+fn encode_address(bytes: &mut Vec<u8>, register: u8, address: &Address) {
+    let base = match address.base {
+        Some(base) => base as u8,
+        None => {
+            bytes.push(modrm(0b00, register, 0b101));
+            bytes.extend_from_slice(&address.displacement.to_le_bytes());
+            return;
+        }
+    };
+
+    let displacement = address.displacement as i32;
+    let mode = match displacement {
+        0 if address.base != Some(GeneralRegister::EBP) => 0b00,
+        -128..=127 => 0b01,
+        _ => 0b10,
+    };
+    bytes.push(modrm(mode, register, base));
+    if address.base == Some(GeneralRegister::ESP) {
+        bytes.push(0x24);
+    }
+    match mode {
+        0b01 => bytes.push(displacement as i8 as u8),
+        0b10 => bytes.extend_from_slice(&address.displacement.to_le_bytes()),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::program::Syntax;
+
+    fn round_trip(src: &str, expected: &[u8]) {
+        let instruction: Instruction = src.parse().unwrap();
+        let bytes = encode(&instruction, false).unwrap();
+        assert_eq!(bytes, expected, "{}", src);
+
+        let decoded = decode(&bytes).unwrap();
+        assert_eq!(decoded.len(), 1);
+        assert_eq!(decoded[0].instruction.to_string(), instruction.to_string());
+    }
+
+    #[test]
+    fn encodes_and_decodes_back() {
+        round_trip("PADDB MM0, MM1", &[0x0F, 0xFC, 0xC1]);
+        round_trip("PMULLD MM7, MM6", &[0x0F, 0xF4, 0xFE]);
+        round_trip("EMMS", &[0x0F, 0x77]);
+        round_trip("MOVD MM2, ECX", &[0x0F, 0x6E, 0xD1]);
+        round_trip("MOVQ [ESP+8], MM1", &[0x0F, 0x7F, 0x4C, 0x24, 0x08]);
+        round_trip("MOVQ [ESP], MM1", &[0x0F, 0x7F, 0x0C, 0x24]);
+        round_trip("MOVQ MM0, [EBP]", &[0x0F, 0x6F, 0x45, 0x00]);
+        round_trip("MOVQ MM0, [EBX-0x80]", &[0x0F, 0x6F, 0x43, 0x80]);
+        round_trip("MOVQ MM0, [EBX+0x80]", &[0x0F, 0x6F, 0x83, 0x80, 0x00, 0x00, 0x00]);
+        round_trip("MOVD [0x12345678], MM3", &[0x0F, 0x7E, 0x1D, 0x78, 0x56, 0x34, 0x12]);
+    }
+
+    #[test]
+    fn matches_the_assembler() {
+        // `as --32` and `objdump -d`, the calculator's PADDSB/PADDSW/PMULLD are PADDUSB/PADDUSW/PMULUDQ.
+        for (src, xmm, expected, objdump) in [
+            ("PADDB MM0, MM1", false, &[0x0F, 0xFC, 0xC1][..], "paddb %mm1, %mm0"),
+            ("PADDQ MM0, MM1", false, &[0x0F, 0xD4, 0xC1][..], "paddq %mm1, %mm0"),
+            ("PADDSB MM1, MM2", false, &[0x0F, 0xDC, 0xCA][..], "paddusb %mm2, %mm1"),
+            ("PADDSW MM1, MM2", false, &[0x0F, 0xDD, 0xCA][..], "paddusw %mm2, %mm1"),
+            ("PMULLD MM7, MM6", false, &[0x0F, 0xF4, 0xFE][..], "pmuludq %mm6, %mm7"),
+            ("PADDSB MM1, MM2", true, &[0x66, 0x0F, 0xDC, 0xCA][..], "paddusb %xmm2, %xmm1"),
+            ("PMULLD MM1, MM2", true, &[0x66, 0x0F, 0xF4, 0xCA][..], "pmuludq %xmm2, %xmm1"),
+            ("MOVD EAX, MM1", true, &[0x66, 0x0F, 0x7E, 0xC8][..], "movd %xmm1, %eax"),
+        ].iter() {
+            let instruction: Instruction = src.parse().unwrap();
+            let bytes = encode(&instruction, *xmm).unwrap();
+            assert_eq!(bytes, *expected, "{}", src);
+            assert_eq!(decode(&bytes).unwrap()[0].format(Syntax::Att), *objdump);
+        }
+    }
+
+    #[test]
+    fn xmm_form_and_unsupported_instructions() {
+        let instruction: Instruction = "PADDSW MM1, MM2".parse().unwrap();
+        assert_eq!(encode(&instruction, true).unwrap(), [0x66, 0x0F, 0xDD, 0xCA]);
+
+        for src in ["PADDB MM0{k=01}, MM1", "PINCB MM0", "MOVQ MM0, MM1"].iter() {
+            let instruction: Instruction = src.parse().unwrap();
+            let xmm = src.starts_with("MOVQ");
+            assert!(matches!(encode(&instruction, xmm), Err(Error::Unsupported(_))), "{}", src);
+        }
+    }
+}
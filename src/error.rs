@@ -0,0 +1,48 @@
+use core::fmt;
+use fmt::{Display, Formatter};
+use alloc::format;
+use alloc::string::String;
+
+/// Every message is complete and in Czech, ready to be shown to the user.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Error {
+    /// Text that isn't a valid number, register, operand, instruction or expression.
+    Parse(String),
+    /// An operation or instruction is missing an operand or input it requires.
+    MissingOperand(String),
+    /// Operands of a wrong kind, a mask where none is allowed or an address outside the memory.
+    InvalidArgument(String),
+    /// Valid input the calculator can't handle, e.g. XMM registers or an operation without an opcode.
+    Unsupported(String),
+    /// The system clipboard couldn't be opened or written, only the GUI uses it.
+    Clipboard(String),
+}
+
+impl Error {
+    /// Prefixes the message, e.g. with a line number, keeping the kind of the error.
+    pub fn context(self, context: impl Display) -> Self {
+        let prefix = |message: String| format!("{}: {}", context, message);
+        match self {
+            Error::Parse(message) => Error::Parse(prefix(message)),
+            Error::MissingOperand(message) => Error::MissingOperand(prefix(message)),
+            Error::InvalidArgument(message) => Error::InvalidArgument(prefix(message)),
+            Error::Unsupported(message) => Error::Unsupported(prefix(message)),
+            Error::Clipboard(message) => Error::Clipboard(prefix(message)),
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Parse(message) |
+            Error::MissingOperand(message) |
+            Error::InvalidArgument(message) |
+            Error::Unsupported(message) => f.write_str(message),
+            Error::Clipboard(message) => write!(f, "Schránku nelze použít: {}", message),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
@@ -1,48 +1,164 @@
-use iced::{Application, Command, Element, TextInput, Text, text_input, button, Button, Settings, Length, HorizontalAlignment, Column, Scrollable, scrollable, Container, Row, Align, Checkbox, Space, Font};
+use iced::{Application, Command, Element, TextInput, Text, text_input, button, Button, Settings, Length, HorizontalAlignment, Column, Scrollable, scrollable, Container, Row, Align, Checkbox, Space, Font, Color};
 use strum::IntoEnumIterator;
-use crate::types::{Operations, u64x1};
-use crate::calculations::*;
-use crate::utils::*;
+use kalkulacka_hw::types::{Operations, u64x1, LaneFlags};
+use kalkulacka_hw::operations::{builtin, operations, Operation};
+use kalkulacka_hw::custom::register_file;
+use kalkulacka_hw::calculations::*;
+use kalkulacka_hw::registers::*;
+use kalkulacka_hw::machine::{GeneralRegister, Machine};
+use kalkulacka_hw::program::{Program, Syntax, Trace};
+use kalkulacka_hw::codegen::Snippet;
+use kalkulacka_hw::error::Error;
+use kalkulacka_hw::utils::*;
 use clipboard::{ClipboardContext, ClipboardProvider};
 use std::fmt::Formatter;
+use std::path::Path;
 use uuid::Uuid;
+use regex::Regex;
 
 pub fn run() {
     App::run(Settings::default());
 }
 
+lazy_static! {
+    static ref REPLACING_REG: Regex = Regex::new(r"[^0-9ABCDEF]").unwrap();
+}
+
+fn cleanup_hex_string(value: String) -> String {
+    REPLACING_REG.replace_all(value.to_uppercase().as_str(), "").to_string()
+}
+
 #[cfg_attr(feature = "debug", derive(Debug))]
 struct App {
-    mm1: String,
-    mm1_state: text_input::State,
-    mm2: String,
-    mm2_state: text_input::State,
-    operation: Operations,
-    result: Result<Vec<CalculationResult>, Vec<String>>,
+    registers: Vec<RegisterInput>,
+    destination: Register,
+    source: Option<Register>,
+    mask: String,
+    mask_state: text_input::State,
+    zero_masking: bool,
+    emms: bool,
+    operation: &'static dyn Operation,
+    operations_file: String,
+    operations_file_state: text_input::State,
+    load_operations_button: button::State,
+    loaded_operations: Option<Result<usize, Error>>,
+    fpu: Option<FpuState>,
+    result: Result<Vec<CalculationResult>, Vec<Error>>,
     calculate_button: button::State,
+    code: Option<Result<String, Error>>,
+    code_button: button::State,
+    code_copied: bool,
+    clipboard_error: Option<Error>,
+    program: String,
+    program_state: text_input::State,
+    trace: Option<Result<Trace, Error>>,
+    step: usize,
+    syntax: Syntax,
+    run_program_button: button::State,
+    step_back_button: button::State,
+    step_forward_button: button::State,
     scroll_state: scrollable::State,
 }
 
 impl Default for App {
     fn default() -> Self {
         Self {
-            mm1: "".to_string(),
-            mm1_state: Default::default(),
-            mm2: "".to_string(),
-            mm2_state: Default::default(),
-            operation: Default::default(),
+            registers: Register::iter().map(RegisterInput::new).collect(),
+            destination: Register::MM1,
+            source: Some(Register::MM2),
+            mask: "".to_string(),
+            mask_state: Default::default(),
+            zero_masking: false,
+            emms: false,
+            operation: builtin(Operations::PADDB),
+            operations_file: "".to_string(),
+            operations_file_state: Default::default(),
+            load_operations_button: Default::default(),
+            loaded_operations: None,
+            fpu: None,
             result: Ok(Vec::new()),
             calculate_button: Default::default(),
+            code: None,
+            code_button: Default::default(),
+            code_copied: false,
+            clipboard_error: None,
+            program: "".to_string(),
+            program_state: Default::default(),
+            trace: None,
+            step: 0,
+            syntax: Syntax::Intel,
+            run_program_button: Default::default(),
+            step_back_button: Default::default(),
+            step_forward_button: Default::default(),
             scroll_state: Default::default(),
         }
     }
 }
 
+impl App {
+    fn register_input(&self, register: Register) -> &str {
+        &self.registers[register as usize].value
+    }
+
+    /// Machine with the entered registers, empty inputs are zero.
+    fn machine(&self) -> Result<Machine, Error> {
+        let mut machine = Machine::default();
+        for input in self.registers.iter().filter(|input| !input.value.is_empty()) {
+            let value = parse_hex(&input.value).map_err(|error| error.context(input.register))?;
+            machine.registers.set(input.register, u64x1::new(value));
+        }
+        Ok(machine)
+    }
+}
+
+fn copy(contents: String) -> Result<(), Error> {
+    let mut clipboard: ClipboardContext = ClipboardProvider::new().map_err(|error| Error::Clipboard(error.to_string()))?;
+    clipboard.set_contents(contents).map_err(|error| Error::Clipboard(error.to_string()))
+}
+
+#[cfg_attr(feature = "debug", derive(Debug))]
+struct RegisterInput {
+    register: Register,
+    value: String,
+    state: text_input::State,
+}
+
+impl RegisterInput {
+    fn new(register: Register) -> Self {
+        Self { register, value: "".to_string(), state: Default::default() }
+    }
+
+    fn view(&mut self) -> Element<'_, Message> {
+        let register = self.register;
+
+        Row::new()
+            .spacing(20)
+            .align_items(Align::Center)
+            .push(
+                Text::new(register.to_string())
+                    .size(30)
+            )
+            .push(
+                TextInput::new(
+                    &mut self.state,
+                    "Chtělo by to zadat hexadecimální číslo.",
+                    &self.value,
+                    move |value| Message::InputChanged(register, value),
+                )
+                    .size(30)
+                    .padding(15)
+            )
+            .into()
+    }
+}
+
 #[cfg_attr(feature = "debug", derive(Debug))]
 struct CalculationResult {
     identifier: Uuid,
     label: Option<String>,
+    previous: Option<String>,
     content: String,
+    lanes: Option<LaneFlags>,
     button_state: button::State,
     copied: bool,
 }
@@ -52,7 +168,9 @@ impl Default for CalculationResult {
         Self {
             identifier: Uuid::new_v4(),
             label: None,
+            previous: None,
             content: "".to_string(),
+            lanes: None,
             button_state: Default::default(),
             copied: false,
         }
@@ -60,19 +178,54 @@ impl Default for CalculationResult {
 }
 
 impl CalculationResult {
-    fn new(label: Option<String>, content: String) -> Self {
-        Self { label, content, ..Default::default() }
+    fn new(label: Option<String>, previous: Option<String>, content: String) -> Self {
+        Self { label, previous, content, ..Default::default() }
+    }
+
+    fn with_lanes(mut self, lanes: Option<LaneFlags>) -> Self {
+        self.lanes = lanes;
+        self
+    }
+
+    fn prefix(&self) -> String {
+        let mut prefix = String::new();
+        if let Some(label) = &self.label {
+            prefix.push_str(&format!("{}: ", label));
+        }
+        if let Some(previous) = &self.previous {
+            prefix.push_str(&format!("{} → ", previous));
+        }
+        prefix
     }
 }
 
 impl CalculationResult {
-    fn view(&mut self) -> Element<Message> {
+    fn view(&mut self) -> Element<'_, Message> {
+        let value: Element<Message> = match &self.lanes {
+            Some(lanes) => {
+                let digits = lanes.bits() / 4;
+                (0..lanes.lanes()).rev().fold(
+                    Row::new()
+                        .push(
+                            Text::new(self.prefix())
+                                .size(25)
+                        ),
+                    |row, index| {
+                        let end = self.content.len() - index * digits;
+                        row.push(
+                            Text::new(&self.content[end - digits..end])
+                                .size(25)
+                                .color(style::lane_color(lanes.status(index)))
+                        )
+                    },
+                ).into()
+            }
+            None => Text::new(self.to_string()).size(25).into(),
+        };
+
         Row::new()
             .push(
-                Container::new(
-                    Text::new(&self.to_string())
-                        .size(25)
-                )
+                Container::new(value)
                     .padding(10)
             )
             .push(
@@ -91,7 +244,7 @@ impl CalculationResult {
                 )
                     .style(style::Button::Copy(self.copied))
                     .padding(10)
-                    .on_press(Message::CopyToClipBoard(self.identifier.clone()))
+                    .on_press(Message::CopyToClipBoard(self.identifier))
             )
             .spacing(20)
             .into()
@@ -100,26 +253,30 @@ impl CalculationResult {
 
 impl std::fmt::Display for CalculationResult {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match &self.label {
-            None => f.write_str(&self.content),
-            Some(label) => f.write_fmt(format_args!("{}: {}", label, self.content)),
-        }
-    }
-}
-
-impl Default for Operations {
-    fn default() -> Self {
-        Operations::PADDB
+        f.write_str(&self.prefix())?;
+        f.write_str(&self.content)
     }
 }
 
 #[derive(Clone, Debug)]
 enum Message {
-    InputMM1Changed(String),
-    InputMM2Changed(String),
-    OperationChanged(Operations),
+    InputChanged(Register, String),
+    DestinationChanged(Register),
+    SourceChanged(Option<Register>),
+    InputMaskChanged(String),
+    ZeroMaskingToggled(bool),
+    EmmsToggled(bool),
+    OperationChanged(&'static dyn Operation),
+    OperationsFileChanged(String),
+    LoadOperations,
     Calculate,
     CopyToClipBoard(Uuid),
+    CopyCode,
+    ProgramChanged(String),
+    RunProgram,
+    StepBack,
+    StepForward,
+    AttSyntaxToggled(bool),
 }
 
 impl Application for App {
@@ -140,54 +297,144 @@ impl Application for App {
 
     fn update(&mut self, message: Message) -> Command<Message> {
         match message {
-            Message::InputMM1Changed(value) => {
-                self.mm1 = cleanup_hex_string(value);
-                self.mm1.truncate(16);
+            Message::InputChanged(register, value) => {
+                let input = &mut self.registers[register as usize];
+                input.value = cleanup_hex_string(value);
+                input.value.truncate(16);
+            }
+            Message::DestinationChanged(register) => {
+                self.destination = register;
+            }
+            Message::SourceChanged(register) => {
+                self.source = register;
             }
-            Message::InputMM2Changed(value) => {
-                self.mm2 = cleanup_hex_string(value);
-                self.mm2.truncate(16);
+            Message::InputMaskChanged(value) => {
+                self.mask = cleanup_hex_string(value);
+                self.mask.truncate(2);
+            }
+            Message::ZeroMaskingToggled(zero_masking) => {
+                self.zero_masking = zero_masking;
+            }
+            Message::EmmsToggled(emms) => {
+                self.emms = emms;
             }
             Message::Calculate => {
                 let mut errors = Vec::new();
 
-                if self.mm1.is_empty() {
-                    errors.push("Register MM1 nesmí být prázdný.".to_string());
+                if self.register_input(self.destination).is_empty() {
+                    errors.push(Error::MissingOperand(format!("Register {} nesmí být prázdný.", self.destination)));
                 }
-                if self.operation.requires_mm2() && self.mm2.is_empty() {
-                    errors.push(format!("Register MM2 nesmí být prázdný při operaci \"{}.\"", self.operation));
+                if self.operation.requires_mm2() {
+                    match self.source {
+                        None => errors.push(Error::MissingOperand(format!("Operace \"{}\" vyžaduje zdrojový registr.", self.operation))),
+                        Some(source) if self.register_input(source).is_empty() => {
+                            errors.push(Error::MissingOperand(format!("Register {} nesmí být prázdný při operaci \"{}.\"", source, self.operation)));
+                        }
+                        _ => {}
+                    }
                 }
-                if errors.is_empty() {
+                let machine = self.machine().map_err(|error| errors.push(error)).ok();
+                let mask = match self.mask.as_str() {
+                    "" => None,
+                    mask => parse_mask(mask).map_err(|error| errors.push(error)).ok().map(|bits| Mask {
+                        bits,
+                        mode: if self.zero_masking { MaskMode::Zero } else { MaskMode::Merge },
+                    }),
+                };
+                let calculation = match machine {
+                    Some(machine) if errors.is_empty() => {
+                        let mut registers = machine.registers;
+                        let before = registers.clone();
+                        registers.calculate(self.operation, self.destination, self.source, mask)
+                            .map_err(|error| errors.push(error))
+                            .ok()
+                            .map(|calculation| (before, registers, calculation))
+                    }
+                    _ => None,
+                };
+
+                if let Some((before, mut registers, calculation)) = calculation {
+                    if self.emms {
+                        registers.emms();
+                    }
+                    let (destination_lanes, source_lanes) = match calculation.lanes {
+                        EitherLanes::OneRegister(x) => (x, None),
+                        EitherLanes::TwoRegisters(x, y) => (x, self.source.map(|source| (source, y))),
+                    };
+
                     self.result = Ok(
-                        match calculate(
-                            u64x1::new(parse_hex(&self.mm1).unwrap()),
-                            if self.mm2.is_empty() {
-                                u64x1::new(0)
-                            } else {
-                                u64x1::new(parse_hex(&self.mm2).unwrap())
-                            },
-                            &self.operation,
-                        ) {
-                            EitherRegisters::OneRegister(x) => {
-                                vec![CalculationResult::new(None, x.to_string())]
-                            }
-                            EitherRegisters::TwoRegisters(x, y) => {
-                                let mut result = Vec::new();
-                                result.push(CalculationResult::new(Some("MM1".to_string()), x.to_string()));
-                                if !self.mm2.is_empty() {
-                                    result.push(CalculationResult::new(Some("MM2".to_string()), y.to_string()));
-                                }
-                                result
-                            }
-                        }
+                        before.iter()
+                            .zip(registers.iter())
+                            .map(|((register, x), (_, y))| {
+                                let lanes = if register == self.destination {
+                                    Some(destination_lanes)
+                                } else {
+                                    source_lanes.filter(|(source, _)| *source == register).map(|(_, lanes)| lanes)
+                                };
+                                CalculationResult::new(Some(register.to_string()), Some(x.to_string()), y.to_string())
+                                    .with_lanes(lanes)
+                            })
+                            .collect()
                     );
+                    self.fpu = Some(registers.fpu().clone());
+                    self.code = match (self.operation.builtin(), self.source, mask) {
+                        (Some(operation), Some(source), None) => {
+                            let snippet = Snippet {
+                                operation,
+                                destination: self.destination,
+                                source,
+                                a: before.get(self.destination),
+                                b: before.get(source),
+                                expected: registers.get(self.destination),
+                            };
+                            Some(snippet.c().and_then(|c| snippet.nasm().map(|nasm| format!("{}\n{}", c, nasm))))
+                        }
+                        _ => None,
+                    };
                 } else {
                     self.result = Err(errors);
+                    self.fpu = None;
+                    self.code = None;
+                }
+                self.code_copied = false;
+                self.clipboard_error = None;
+            }
+            Message::ProgramChanged(program) => {
+                self.program = program;
+            }
+            Message::RunProgram => {
+                self.trace = Some(self.machine().and_then(|mut machine| {
+                    self.program.parse::<Program>().map(|program| program.trace(&mut machine))
+                }));
+                self.step = 0;
+            }
+            Message::StepBack => {
+                self.step = self.step.saturating_sub(1);
+            }
+            Message::StepForward => {
+                if let Some(Ok(trace)) = &self.trace {
+                    self.step = (self.step + 1).min(trace.steps.len());
                 }
             }
+            Message::AttSyntaxToggled(att) => {
+                self.syntax = if att { Syntax::Att } else { Syntax::Intel };
+            }
             Message::OperationChanged(operation) => {
                 self.operation = operation;
             }
+            Message::OperationsFileChanged(path) => {
+                self.operations_file = path;
+            }
+            Message::LoadOperations => {
+                self.loaded_operations = Some(register_file(Path::new(&self.operations_file)).map(|operations| operations.len()));
+            }
+            Message::CopyCode => {
+                if let Some(Ok(code)) = &self.code {
+                    let copied = copy(code.clone());
+                    self.code_copied = copied.is_ok();
+                    self.clipboard_error = copied.err();
+                }
+            }
             Message::CopyToClipBoard(value) => {
                 if let Ok(results) = &mut self.result {
                     let res = results.iter_mut().filter_map(|result| {
@@ -200,8 +447,7 @@ impl Application for App {
                         }
                     }).collect::<String>();
 
-                    let mut clipboard: ClipboardContext = ClipboardProvider::new().unwrap();
-                    clipboard.set_contents(res);
+                    self.clipboard_error = copy(res).err();
                 }
             }
         };
@@ -209,14 +455,14 @@ impl Application for App {
         Command::none()
     }
 
-    fn view(&mut self) -> Element<Message> {
+    fn view(&mut self) -> Element<'_, Message> {
         let title = Text::new("Kalkulačka!")
             .width(Length::Fill)
             .size(100)
             .color([0.5, 0.5, 0.5])
             .horizontal_alignment(HorizontalAlignment::Center);
 
-        let operations: Column<Message> = Operations::iter().fold(
+        let operations: Column<Message> = operations().into_iter().fold(
             Column::new()
                 .push(
                     Text::new("Operace")
@@ -228,47 +474,153 @@ impl Application for App {
                         self.operation == operation,
                         operation.to_string(),
                         move |_checked| {
-                            Message::OperationChanged(operation.clone())
+                            Message::OperationChanged(operation)
                         },
                     ).text_size(25)
                 )
             },
-        );
-
-        let mm1 = Row::new()
-            .spacing(20)
-            .align_items(Align::Center)
+        )
             .push(
-                Text::new("MM1")
-                    .size(30)
+                Text::new(self.operation.metadata().description.as_ref())
+                    .size(18)
             )
             .push(
-                TextInput::new(
-                    &mut self.mm1_state,
-                    "Chtělo by to zadat hexadecimální číslo.",
-                    &self.mm1,
-                    Message::InputMM1Changed,
-                )
-                    .size(30)
-                    .padding(15)
+                Text::new(self.operation.metadata().pseudocode.as_ref())
+                    .size(18)
+            )
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .align_items(Align::Center)
+                    .push(
+                        TextInput::new(
+                            &mut self.operations_file_state,
+                            "Soubor s vlastními operacemi",
+                            &self.operations_file,
+                            Message::OperationsFileChanged,
+                        )
+                            .size(18)
+                            .padding(5)
+                    )
+                    .push(
+                        Button::new(
+                            &mut self.load_operations_button,
+                            Text::new("Načíst")
+                                .size(18),
+                        )
+                            .style(style::Button::Default)
+                            .padding(5)
+                            .on_press(Message::LoadOperations)
+                    )
+            )
+            .push::<Element<Message>>(
+                match &self.loaded_operations {
+                    Some(Ok(count)) => Text::new(format!("Načteno operací: {}", count)).size(18).into(),
+                    Some(Err(error)) => Text::new(error.to_string()).size(18).color(style::WARNING).into(),
+                    None => Column::new().into(),
+                }
             );
 
-        let mm2 = Row::new()
+        let destination = self.destination;
+        let destinations = Register::iter().fold(
+            Row::new()
+                .spacing(10)
+                .align_items(Align::Center)
+                .push(
+                    Text::new("Cíl")
+                        .size(25)
+                ),
+            |row, register| {
+                row.push(
+                    Checkbox::new(
+                        destination == register,
+                        register.to_string(),
+                        move |_checked| Message::DestinationChanged(register),
+                    ).text_size(20)
+                )
+            },
+        );
+
+        let source = self.source;
+        let sources = Register::iter().fold(
+            Row::new()
+                .spacing(10)
+                .align_items(Align::Center)
+                .push(
+                    Text::new("Zdroj")
+                        .size(25)
+                ),
+            |row, register| {
+                row.push(
+                    Checkbox::new(
+                        source == Some(register),
+                        register.to_string(),
+                        move |checked| Message::SourceChanged(if checked { Some(register) } else { None }),
+                    ).text_size(20)
+                )
+            },
+        );
+
+        let mask = Row::new()
             .spacing(20)
             .align_items(Align::Center)
             .push(
-                Text::new("MM2")
-                    .size(30)
+                Text::new("Maska")
+                    .size(25)
             )
             .push(
                 TextInput::new(
-                    &mut self.mm2_state,
-                    "Chtělo by to zadat hexadecimální číslo.",
-                    &self.mm2,
-                    Message::InputMM2Changed,
+                    &mut self.mask_state,
+                    "Všechny pruhy",
+                    &self.mask,
+                    Message::InputMaskChanged,
                 )
-                    .size(30)
-                    .padding(15)
+                    .size(25)
+                    .padding(10)
+                    .width(Length::Units(160))
+            )
+            .push(
+                Checkbox::new(
+                    self.zero_masking,
+                    "Nulovat maskované pruhy".to_string(),
+                    Message::ZeroMaskingToggled,
+                ).text_size(20)
+            );
+
+        let emms = Checkbox::new(
+            self.emms,
+            "Po operaci provést EMMS".to_string(),
+            Message::EmmsToggled,
+        ).text_size(20);
+
+        let fpu = match &self.fpu {
+            Some(fpu) => {
+                let status = Column::new()
+                    .spacing(5)
+                    .push(
+                        Text::new(format!("Stav x87: TOP = {}, tag word = {:04X}", fpu.top(), fpu.tag_word()))
+                            .size(20)
+                    );
+                match fpu.tainted_st(0) {
+                    Some(register) => status.push(
+                        Text::new(format!(
+                            "Varování: po operaci chybí EMMS, následující instrukce x87 čtoucí ST(0) by četla data registru {}.",
+                            register
+                        ))
+                            .size(20)
+                            .color(style::WARNING)
+                    ),
+                    None => status,
+                }
+            }
+            None => Column::new(),
+        };
+
+        let registers = self.registers.iter_mut()
+            .fold(
+                Column::new()
+                    .spacing(20),
+                |column, input| column.push(input.view()),
             );
 
         let calculate = Button::new(
@@ -280,6 +632,85 @@ impl Application for App {
             .padding(10)
             .on_press(Message::Calculate);
 
+        let code_button = Button::new(
+            &mut self.code_button,
+            Text::new(if self.code_copied { "Kód zkopírován" } else { "Kopírovat jako kód (C a NASM)" })
+                .size(20),
+        )
+            .style(style::Button::Copy(self.code_copied))
+            .padding(10);
+        let code: Element<Message> = match &self.code {
+            Some(Ok(_)) => code_button.on_press(Message::CopyCode).into(),
+            Some(Err(error)) => Text::new(error.to_string()).size(20).into(),
+            None => Column::new().into(),
+        };
+
+        let steps = match &self.trace {
+            Some(Ok(trace)) => trace.steps.len(),
+            _ => 0,
+        };
+        let step_back = Button::new(
+            &mut self.step_back_button,
+            Text::new("Zpět")
+                .size(25),
+        )
+            .style(style::Button::Default)
+            .padding(10);
+        let step_forward = Button::new(
+            &mut self.step_forward_button,
+            Text::new("Vpřed")
+                .size(25),
+        )
+            .style(style::Button::Default)
+            .padding(10);
+
+        let program = Column::new()
+            .spacing(10)
+            .push(
+                Text::new("Program")
+                    .size(40)
+            )
+            .push(
+                TextInput::new(
+                    &mut self.program_state,
+                    "PADDB MM1, MM2 / pmullw %mm3, %mm1",
+                    &self.program,
+                    Message::ProgramChanged,
+                )
+                    .size(25)
+                    .padding(10)
+            )
+            .push(
+                Row::new()
+                    .spacing(20)
+                    .push(
+                        Button::new(
+                            &mut self.run_program_button,
+                            Text::new("Spustit")
+                                .size(25),
+                        )
+                            .style(style::Button::Default)
+                            .padding(10)
+                            .on_press(Message::RunProgram)
+                    )
+                    .push(if self.step > 0 { step_back.on_press(Message::StepBack) } else { step_back })
+                    .push(if self.step < steps { step_forward.on_press(Message::StepForward) } else { step_forward })
+                    .push(
+                        Checkbox::new(
+                            self.syntax == Syntax::Att,
+                            "Syntaxe AT&T".to_string(),
+                            Message::AttSyntaxToggled,
+                        ).text_size(20)
+                    )
+            )
+            .push::<Element<Message>>(
+                match &self.trace {
+                    Some(Ok(trace)) => trace_view(trace, self.step, self.syntax).into(),
+                    Some(Err(error)) => Text::new(error.to_string()).size(25).color(style::WARNING).into(),
+                    None => Column::new().into(),
+                }
+            );
+
         let result = Column::new()
             .push(
                 Text::new("Výsledek")
@@ -295,8 +726,19 @@ impl Application for App {
                                 |column, result|
                                     column.push(result.view()
                                     ),
-                            ).into(),
-                    Err(errors) => Text::new(errors.join("\n")).size(25).into(),
+                            )
+                            .push(
+                                Text::new("Pruhy s přetečením jsou červené, se saturací oranžové a maskované šedé.")
+                                    .size(18)
+                            )
+                            .into(),
+                    Err(errors) => Text::new(errors.iter().map(Error::to_string).collect::<Vec<_>>().join("\n")).size(25).into(),
+                }
+            )
+            .push::<Element<Message>>(
+                match &self.clipboard_error {
+                    Some(error) => Text::new(error.to_string()).size(20).color(style::WARNING).into(),
+                    None => Column::new().into(),
                 }
             )
             .spacing(10)
@@ -313,11 +755,18 @@ impl Application for App {
                         Column::new()
                             .width(Length::FillPortion(3))
                             .spacing(20)
-                            .push(mm1)
-                            .push(mm2)
+                            .push(registers)
+                            .push(destinations)
+                            .push(sources)
+                            .push(mask)
+                            .push(emms)
                             .push(Container::new(calculate).width(Length::Fill).center_x())
                             .push(Space::new(Length::Fill, Length::Units(30)))
                             .push(result)
+                            .push(code)
+                            .push(fpu)
+                            .push(Space::new(Length::Fill, Length::Units(30)))
+                            .push(program)
                     )
                     .push(
                         operations
@@ -337,6 +786,54 @@ impl Application for App {
     }
 }
 
+/// Registers after `step` instructions, the ones changed by the step are highlighted.
+fn trace_view<'a>(trace: &Trace, step: usize, syntax: Syntax) -> Column<'a, Message> {
+    // A new trace can be shorter than the step shown for the previous one.
+    let step = step.min(trace.steps.len());
+    let state = trace.state(step).unwrap_or(&trace.initial);
+    let previous = trace.state(step.saturating_sub(1)).unwrap_or(&trace.initial);
+    let title = match step {
+        0 => format!("Krok 0/{}: počáteční stav", trace.steps.len()),
+        step => format!("Krok {}/{}: {}", step, trace.steps.len(), trace.steps[step - 1].instruction.format(syntax)),
+    };
+
+    let column = Register::iter().fold(
+        Column::new()
+            .spacing(5)
+            .push(
+                Text::new(title)
+                    .size(25)
+            ),
+        |column, register| {
+            let value = state.registers.get(register);
+            column.push(
+                Text::new(format!("{}: {}", register, value))
+                    .size(25)
+                    .color(if value == previous.registers.get(register) { Color::BLACK } else { style::WARNING })
+            )
+        },
+    );
+    let column = GeneralRegister::iter()
+        .filter(|register| state.general(*register) != 0 || previous.general(*register) != 0)
+        .fold(column, |column, register| {
+            let value = state.general(register);
+            column.push(
+                Text::new(format!("{}: {:08X}", register, value))
+                    .size(25)
+                    .color(if value == previous.general(register) { Color::BLACK } else { style::WARNING })
+            )
+        });
+
+    match &trace.error {
+        Some(error) if step == trace.steps.len() => column.push(
+            Text::new(error.to_string())
+                .size(20)
+                .color(style::WARNING)
+        ),
+        _ => column,
+    }
+}
+
 const ICONS: Font = Font::External {
     name: "IcoFont",
     bytes: include_bytes!("../../fonts/icofont.ttf"),
@@ -344,6 +841,18 @@ const ICONS: Font = Font::External {
 
 mod style {
     use iced::{button, Background, Color, Vector};
+    use kalkulacka_hw::types::LaneStatus;
+
+    pub const WARNING: Color = Color { r: 0.78, g: 0.0, b: 0.08, a: 1.0 };
+
+    pub fn lane_color(status: LaneStatus) -> Color {
+        match status {
+            LaneStatus::Unchanged => Color::BLACK,
+            LaneStatus::Overflowed => WARNING,
+            LaneStatus::SaturatedHigh | LaneStatus::SaturatedLow => Color::from_rgb8(230, 120, 0),
+            LaneStatus::Masked => Color::from_rgb8(150, 150, 150),
+        }
+    }
 
     pub enum Button {
         Default,
@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+use std::fmt;
+use fmt::{Display, Formatter};
+use crate::types::*;
+use crate::calculations::*;
+use crate::error::Error;
+
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub enum Intrinsic {
+    Operation(Operations),
+    SetZero,
+    /// Every lane set to the same value.
+    Set1(usize),
+    /// Lanes listed from the highest one, as in C.
+    Set(usize),
+    /// Lanes listed from lane 0.
+    SetR(usize),
+    /// Zero-extends a 32-bit value.
+    Cvtsi32,
+}
+
+/// Intrinsics which don't compute an operation, those come from the operations' metadata.
+pub const INTRINSICS: [(&str, Intrinsic); 13] = [
+    ("_mm_setzero_si64", Intrinsic::SetZero),
+    ("_mm_set1_pi8", Intrinsic::Set1(8)),
+    ("_mm_set1_pi16", Intrinsic::Set1(16)),
+    ("_mm_set1_pi32", Intrinsic::Set1(32)),
+    ("_mm_set_pi8", Intrinsic::Set(8)),
+    ("_mm_set_pi16", Intrinsic::Set(16)),
+    ("_mm_set_pi32", Intrinsic::Set(32)),
+    ("_mm_setr_pi8", Intrinsic::SetR(8)),
+    ("_mm_setr_pi16", Intrinsic::SetR(16)),
+    ("_mm_setr_pi32", Intrinsic::SetR(32)),
+    ("_mm_cvtsi32_si64", Intrinsic::Cvtsi32),
+    ("_m_from_int", Intrinsic::Cvtsi32),
+    ("_mm_cvtsi64_m64", Intrinsic::Set(64)),
+];
+
+impl Intrinsic {
+    pub fn from_name(name: &str) -> Option<Self> {
+        let operation = Operations::ALL.iter()
+            .find(|operation| operation.metadata().hardware.as_ref().is_some_and(|hardware| hardware.intrinsics.contains(&name)));
+        match operation {
+            Some(operation) => Some(Intrinsic::Operation(*operation)),
+            None => INTRINSICS.iter().find(|(candidate, _)| *candidate == name).map(|(_, intrinsic)| *intrinsic),
+        }
+    }
+
+    /// The first name is the one used for printing.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Intrinsic::Operation(operation) => operation.metadata().hardware.as_ref().map_or("", |hardware| hardware.intrinsics[0]),
+            _ => INTRINSICS.iter().find(|(_, intrinsic)| intrinsic == self).map_or("", |(name, _)| name),
+        }
+    }
+
+    pub fn arguments(&self) -> usize {
+        match self {
+            Intrinsic::Operation(_) => 2,
+            Intrinsic::SetZero => 0,
+            Intrinsic::Set1(_) | Intrinsic::Cvtsi32 => 1,
+            Intrinsic::Set(bits) | Intrinsic::SetR(bits) => 64 / bits,
+        }
+    }
+
+    /// Checks the number of arguments too, an `Expression` can be built without the parser.
+    fn apply(&self, arguments: &[u64x1]) -> Result<u64x1, Error> {
+        if arguments.len() != self.arguments() {
+            return Err(Error::InvalidArgument(format!("Funkce {} očekává počet argumentů {}, zadáno {}.", self.name(), self.arguments(), arguments.len())));
+        }
+        let scalar = |index: usize| arguments[index].lane(0, 64);
+
+        Ok(match self {
+            Intrinsic::Operation(operation) => match calculate(arguments[0], arguments.get(1).copied(), operation, None)?.registers {
+                EitherRegisters::OneRegister(x) | EitherRegisters::TwoRegisters(x, _) => x,
+            },
+            Intrinsic::SetZero => u64x1::default(),
+            Intrinsic::Set1(bits) => (0..64 / bits).fold(u64x1::default(), |register, index| register.with_lane(index, *bits, scalar(0))),
+            Intrinsic::Set(bits) => {
+                let lanes = 64 / bits;
+                (0..lanes).fold(u64x1::default(), |register, index| register.with_lane(index, *bits, scalar(lanes - 1 - index)))
+            }
+            Intrinsic::SetR(bits) => (0..64 / bits).fold(u64x1::default(), |register, index| register.with_lane(index, *bits, scalar(index))),
+            Intrinsic::Cvtsi32 => u64x1::default().with_lane(0, 32, scalar(0)),
+        })
+    }
+}
+
+/// Nested intrinsic calls over named `__m64` inputs and integer literals.
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub enum Expression {
+    Number(u64),
+    Input(String),
+    Call(Intrinsic, Vec<Expression>),
+}
+
+impl Expression {
+    pub fn evaluate(&self, inputs: &HashMap<String, u64x1>) -> Result<u64x1, Error> {
+        match self {
+            Expression::Number(value) => Ok(u64x1::new(*value)),
+            Expression::Input(name) => inputs.get(name).copied().ok_or_else(|| Error::MissingOperand(format!("Neznámý vstup \"{}\".", name))),
+            Expression::Call(intrinsic, arguments) => {
+                let arguments = arguments.iter()
+                    .map(|argument| argument.evaluate(inputs))
+                    .collect::<Result<Vec<_>, _>>()?;
+                intrinsic.apply(&arguments)
+            }
+        }
+    }
+}
+
+impl Display for Expression {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Expression::Number(value) if (*value as i64) < 0 => write!(f, "{}", *value as i64),
+            Expression::Number(value) if *value < 10 => write!(f, "{}", value),
+            Expression::Number(value) => write!(f, "{:#X}", value),
+            Expression::Input(name) => f.write_str(name),
+            Expression::Call(intrinsic, arguments) => {
+                write!(f, "{}(", intrinsic.name())?;
+                for (index, argument) in arguments.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}", argument)?;
+                }
+                f.write_str(")")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluate(src: &str, inputs: &[(&str, u64)]) -> Result<u64, Error> {
+        let inputs = inputs.iter().map(|(name, value)| (name.to_string(), u64x1::new(*value))).collect();
+        Ok(src.parse::<Expression>()?.evaluate(&inputs)?.value())
+    }
+
+    #[test]
+    fn evaluates_nested_calls() {
+        assert_eq!(evaluate("_mm_add_pi8(_mm_set1_pi8(3), a)", &[("a", 0xFF01)]).unwrap(), 0x0303_0303_0303_0204);
+        assert_eq!(evaluate("_mm_set_pi16(1, 2, 3, -1)", &[]).unwrap(), 0x0001_0002_0003_FFFF);
+        assert_eq!(evaluate("_mm_setr_pi32(0x10, 2)", &[]).unwrap(), 0x0000_0002_0000_0010);
+        assert_eq!(evaluate("_m_paddusb(a, _mm_cvtsi32_si64(0xFF))", &[("a", 0x0102)]).unwrap(), 0x01FF);
+        assert_eq!(evaluate("_mm_mul_su32(_mm_setzero_si64(), b)", &[("b", 7)]).unwrap(), 0);
+    }
+
+    #[test]
+    fn display_round_trip() {
+        let src = "_mm_adds_pu16(_mm_set1_pi16(-1), _mm_cvtsi64_m64(0x1234))";
+        let expression: Expression = src.parse().unwrap();
+        assert_eq!(expression.to_string(), src);
+        assert!(src.parse::<Expression>() == expression.to_string().parse());
+    }
+
+    #[test]
+    fn invalid_expressions() {
+        assert!(matches!(evaluate("_mm_add_pi8(a)", &[("a", 0)]), Err(Error::InvalidArgument(_))));
+        assert!(matches!(evaluate("_mm_add_pi8(a, b)", &[("a", 0)]), Err(Error::MissingOperand(_))));
+        assert!(matches!(evaluate("_mm_sub_pi8(a, a)", &[("a", 0)]), Err(Error::Parse(_))));
+        assert!(matches!(evaluate("_mm_setzero_si64() a", &[]), Err(Error::Parse(_))));
+
+        for (intrinsic, arguments) in [(Intrinsic::Set(8), 7), (Intrinsic::Operation(Operations::PADDB), 1), (Intrinsic::SetZero, 1)].iter() {
+            let expression = Expression::Call(*intrinsic, vec![Expression::Number(1); *arguments]);
+            assert!(matches!(expression.evaluate(&HashMap::new()), Err(Error::InvalidArgument(_))), "{}", intrinsic.name());
+        }
+    }
+}
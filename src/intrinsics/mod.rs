@@ -0,0 +1,5 @@
+#[allow(clippy::module_inception)]
+mod intrinsics;
+mod parser;
+
+pub use intrinsics::*;
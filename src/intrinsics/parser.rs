@@ -0,0 +1,101 @@
+use std::str::FromStr;
+use crate::intrinsics::{Expression, Intrinsic};
+use crate::utils::parse_number;
+use crate::error::Error;
+
+/// Parses e.g. `_mm_add_pi8(_mm_set1_pi8(3), a)`, numbers may be negative or hexadecimal.
+impl FromStr for Expression {
+    type Err = Error;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser { tokens: tokenize(src)?, position: 0 };
+        let expression = parser.expression()?;
+        match parser.next() {
+            None => Ok(expression),
+            Some(token) => Err(Error::Parse(format!("Neočekávaný text \"{}\" za koncem výrazu.", token))),
+        }
+    }
+}
+
+fn tokenize(src: &str) -> Result<Vec<String>, Error> {
+    let mut tokens = Vec::new();
+    let mut chars = src.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '(' | ')' | ',' => tokens.push(c.to_string()),
+            c if c.is_whitespace() => {}
+            c if c.is_ascii_alphanumeric() || c == '_' || c == '-' => {
+                let mut token = c.to_string();
+                while let Some(c) = chars.peek().filter(|c| c.is_ascii_alphanumeric() || **c == '_') {
+                    token.push(*c);
+                    chars.next();
+                }
+                tokens.push(token);
+            }
+            c => return Err(Error::Parse(format!("Neočekávaný znak \"{}\".", c))),
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<String>,
+    position: usize,
+}
+
+impl Parser {
+    fn next(&mut self) -> Option<&str> {
+        let token = self.tokens.get(self.position)?;
+        self.position += 1;
+        Some(token)
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<(), Error> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => Err(Error::Parse(format!("Očekáváno \"{}\", ne \"{}\".", expected, token))),
+            None => Err(Error::Parse(format!("Očekáváno \"{}\", výraz končí.", expected))),
+        }
+    }
+
+    fn expression(&mut self) -> Result<Expression, Error> {
+        let token = self.next().ok_or_else(|| Error::Parse("Chybí výraz.".to_string()))?.to_string();
+
+        if token.starts_with(|c: char| c.is_ascii_digit() || c == '-') {
+            let (negative, number) = match token.strip_prefix('-') {
+                Some(number) => (true, number),
+                None => (false, token.as_str()),
+            };
+            let value = parse_number(number).map_err(|_| Error::Parse(format!("Neplatné číslo \"{}\".", token)))?;
+            return Ok(Expression::Number(if negative { value.wrapping_neg() } else { value }));
+        }
+
+        if self.tokens.get(self.position).map(String::as_str) != Some("(") {
+            return Ok(Expression::Input(token));
+        }
+
+        let intrinsic = Intrinsic::from_name(&token).ok_or_else(|| Error::Parse(format!("Neznámá funkce \"{}\".", token)))?;
+        self.expect("(")?;
+        let mut arguments = Vec::new();
+        if self.tokens.get(self.position).map(String::as_str) == Some(")") {
+            self.position += 1;
+        } else {
+            loop {
+                arguments.push(self.expression()?);
+                match self.next() {
+                    Some(",") => {}
+                    Some(")") => break,
+                    Some(token) => return Err(Error::Parse(format!("Očekáváno \",\" nebo \")\", ne \"{}\".", token))),
+                    None => return Err(Error::Parse("Chybí \")\".".to_string())),
+                }
+            }
+        }
+
+        if arguments.len() != intrinsic.arguments() {
+            return Err(Error::InvalidArgument(format!("Funkce {} očekává počet argumentů {}, zadáno {}.", token, intrinsic.arguments(), arguments.len())));
+        }
+        Ok(Expression::Call(intrinsic, arguments))
+    }
+}
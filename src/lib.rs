@@ -0,0 +1,55 @@
+//! Lane arithmetic of 64-bit MMX registers, the CLI and GUI of `kalkulacka_hw` are built on top of it.
+//!
+//! ```
+//! use kalkulacka_hw::{calculate, EitherRegisters, Operations, u64x1};
+//!
+//! let calculation = calculate(u64x1::new(0x7F), Some(u64x1::new(0x01)), &Operations::PADDB, None).unwrap();
+//! match calculation.registers {
+//!     EitherRegisters::OneRegister(result) => assert_eq!(result.lane(0, 8), 0x80),
+//!     EitherRegisters::TwoRegisters(..) => unreachable!(),
+//! }
+//! ```
+//!
+//! Without the default `std` feature the crate is `no_std` with only `error`, `types`, `calculations`,
+//! `operations` (without the registry) and `utils`. Error messages need `alloc`, the arithmetic itself doesn't allocate.
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+pub mod error;
+pub mod types;
+pub mod calculations;
+pub mod operations;
+pub mod utils;
+#[cfg(feature = "serde")]
+pub mod serialization;
+#[cfg(feature = "std")]
+pub mod custom;
+#[cfg(feature = "std")]
+pub mod registers;
+#[cfg(feature = "std")]
+pub mod machine;
+#[cfg(feature = "std")]
+pub mod program;
+#[cfg(feature = "std")]
+pub mod encoding;
+#[cfg(feature = "std")]
+pub mod intrinsics;
+#[cfg(feature = "std")]
+pub mod codegen;
+#[cfg(all(feature = "std", target_arch = "x86_64"))]
+pub mod verification;
+
+pub use error::Error;
+pub use types::{u64x1, u32x2, u16x4, u8x8, LaneFlags, LaneStatus, LaneWidth, Metadata, Operations};
+pub use calculations::{calculate, Calculation, EitherLanes, EitherRegisters, Mask, MaskMode};
+pub use operations::{builtin, Operation};
+#[cfg(feature = "std")]
+pub use operations::{find, operations, register};
+#[cfg(feature = "std")]
+pub use registers::{Register, RegisterFile};
+#[cfg(feature = "std")]
+pub use machine::{Instruction, Machine};
+#[cfg(feature = "std")]
+pub use program::{Program, Syntax};
@@ -0,0 +1,244 @@
+use std::fmt;
+use fmt::{Display, Formatter};
+use std::str::FromStr;
+use crate::types::*;
+use crate::calculations::*;
+use crate::registers::*;
+use crate::operations::{find, Operation};
+use crate::machine::{Address, GeneralRegister, Memory, Operand};
+use crate::error::Error;
+
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(any(feature = "debug", feature = "gui"), derive(Debug))]
+pub enum Mnemonic {
+    Operation(&'static dyn Operation),
+    Movd,
+    Movq,
+}
+
+impl Display for Mnemonic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Mnemonic::Operation(operation) => write!(f, "{}", operation),
+            Mnemonic::Movd => f.write_str("MOVD"),
+            Mnemonic::Movq => f.write_str("MOVQ"),
+        }
+    }
+}
+
+impl FromStr for Mnemonic {
+    type Err = Error;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        match src.trim().to_uppercase().as_str() {
+            "MOVD" => Ok(Mnemonic::Movd),
+            "MOVQ" => Ok(Mnemonic::Movq),
+            name => find(name)
+                .map(Mnemonic::Operation)
+                .ok_or_else(|| Error::Parse(format!("Neznámá operace \"{}\".", src.trim()))),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub enum Instruction {
+    /// Intel operand order: the result of `operation` is written into `destination`.
+    Operation {
+        operation: &'static dyn Operation,
+        destination: Register,
+        source: Option<Register>,
+        mask: Option<Mask>,
+    },
+    /// Moves 32 bits, zero-extended when loading an MMX register.
+    Movd {
+        destination: Operand,
+        source: Operand,
+    },
+    Movq {
+        destination: Operand,
+        source: Operand,
+    },
+    Emms,
+}
+
+impl Instruction {
+    /// Builds an instruction, checking the operand kinds the mnemonic accepts.
+    pub fn new(mnemonic: Mnemonic, destination: Operand, source: Option<Operand>, mask: Option<Mask>) -> Result<Self, Error> {
+        if mask.is_some() && !matches!(mnemonic, Mnemonic::Operation(_)) {
+            return Err(Error::InvalidArgument(format!("Masku nelze použít s instrukcí {}.", mnemonic)));
+        }
+
+        match (mnemonic, destination, source) {
+            (Mnemonic::Operation(operation), Operand::Mmx(destination), source) => {
+                let source = match source {
+                    Some(Operand::Mmx(source)) => Some(source),
+                    Some(source) => {
+                        return Err(Error::InvalidArgument(format!("Operace {} vyžaduje jako zdroj registr MMX, ne \"{}\".", operation, source)));
+                    }
+                    None if operation.requires_mm2() => {
+                        return Err(Error::MissingOperand(format!("Operace {} vyžaduje zdrojový registr.", operation)));
+                    }
+                    None => None,
+                };
+                Ok(Instruction::Operation { operation, destination, source, mask })
+            }
+            (Mnemonic::Operation(operation), destination, _) => {
+                Err(Error::InvalidArgument(format!("Operace {} vyžaduje jako cíl registr MMX, ne \"{}\".", operation, destination)))
+            }
+            (Mnemonic::Movd, destination, Some(source)) => match (destination, source) {
+                (Operand::Mmx(_), Operand::General(_)) | (Operand::Mmx(_), Operand::Memory(_)) |
+                (Operand::General(_), Operand::Mmx(_)) | (Operand::Memory(_), Operand::Mmx(_)) => {
+                    Ok(Instruction::Movd { destination, source })
+                }
+                _ => Err(Error::InvalidArgument("MOVD přesouvá data mezi registrem MMX a 32bitovým registrem nebo pamětí.".to_string())),
+            },
+            (Mnemonic::Movq, destination, Some(source)) => match (destination, source) {
+                (Operand::Mmx(_), Operand::Mmx(_)) | (Operand::Mmx(_), Operand::Memory(_)) | (Operand::Memory(_), Operand::Mmx(_)) => {
+                    Ok(Instruction::Movq { destination, source })
+                }
+                _ => Err(Error::InvalidArgument("MOVQ přesouvá data mezi registrem MMX a jiným registrem MMX nebo pamětí.".to_string())),
+            },
+            (mnemonic, _, None) => Err(Error::MissingOperand(format!("Instrukce {} vyžaduje zdrojový operand.", mnemonic))),
+        }
+    }
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Instruction::Operation { operation, destination, source, mask } => {
+                write!(f, "{} {}", operation, destination)?;
+                if let Some(mask) = mask {
+                    write!(f, "{}", mask)?;
+                }
+                match source {
+                    Some(source) => write!(f, ", {}", source),
+                    None => Ok(()),
+                }
+            }
+            Instruction::Movd { destination, source } => write!(f, "MOVD {}, {}", destination, source),
+            Instruction::Movq { destination, source } => write!(f, "MOVQ {}, {}", destination, source),
+            Instruction::Emms => f.write_str("EMMS"),
+        }
+    }
+}
+
+/// MMX register file together with the general-purpose registers and memory it exchanges data with.
+#[derive(Clone, Default)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct Machine {
+    pub registers: RegisterFile,
+    general: [u32; 8],
+    pub memory: Memory,
+}
+
+impl Machine {
+    pub fn general(&self, register: GeneralRegister) -> u32 {
+        self.general[register as usize]
+    }
+
+    pub fn set_general(&mut self, register: GeneralRegister, value: u32) {
+        self.general[register as usize] = value;
+    }
+
+    fn address(&self, address: &Address) -> usize {
+        let base = address.base.map_or(0, |base| self.general(base));
+        base.wrapping_add(address.displacement) as usize
+    }
+
+    /// Returns the calculation for lane-wise operations, `None` for data movement.
+    pub fn execute(&mut self, instruction: &Instruction) -> Result<Option<Calculation>, Error> {
+        match instruction {
+            Instruction::Operation { operation, destination, source, mask } => {
+                Ok(Some(self.registers.calculate(*operation, *destination, *source, *mask)?))
+            }
+            Instruction::Movd { destination, source } => {
+                let value = match source {
+                    Operand::Mmx(register) => self.registers.mmx_read(*register).lane(0, 32) as u32,
+                    Operand::General(register) => self.general(*register),
+                    Operand::Memory(address) => self.memory.read_u32(self.address(address))?,
+                };
+                match destination {
+                    Operand::Mmx(register) => self.registers.mmx_write(*register, u64x1::new(value as u64)),
+                    Operand::General(register) => self.set_general(*register, value),
+                    Operand::Memory(address) => {
+                        let address = self.address(address);
+                        self.memory.write_u32(address, value)?;
+                    }
+                }
+                Ok(None)
+            }
+            Instruction::Movq { destination, source } => {
+                let value = match source {
+                    Operand::Mmx(register) => self.registers.mmx_read(*register),
+                    Operand::Memory(address) => self.memory.read_u64(self.address(address))?,
+                    Operand::General(register) => {
+                        return Err(Error::InvalidArgument(format!("MOVQ nemůže číst 32bitový registr {}.", register)));
+                    }
+                };
+                match destination {
+                    Operand::Mmx(register) => self.registers.mmx_write(*register, value),
+                    Operand::Memory(address) => {
+                        let address = self.address(address);
+                        self.memory.write_u64(address, value)?;
+                    }
+                    Operand::General(register) => {
+                        return Err(Error::InvalidArgument(format!("MOVQ nemůže zapisovat do 32bitového registru {}.", register)));
+                    }
+                }
+                Ok(None)
+            }
+            Instruction::Emms => {
+                self.registers.emms();
+                Ok(None)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn movq(destination: &str, source: &str) -> Instruction {
+        Instruction::new(Mnemonic::Movq, destination.parse().unwrap(), Some(source.parse().unwrap()), None).unwrap()
+    }
+
+    #[test]
+    fn movq_stores_and_loads_little_endian() {
+        let mut machine = Machine::default();
+        machine.registers.set(Register::MM1, u64x1::new(0x0807_0605_0403_0201));
+        machine.set_general(GeneralRegister::EBX, 0x10);
+
+        machine.execute(&movq("[EBX+8]", "MM1")).unwrap();
+        assert_eq!(machine.memory.bytes()[0x18..0x20], [1, 2, 3, 4, 5, 6, 7, 8]);
+
+        machine.execute(&movq("MM5", "[0x18]")).unwrap();
+        assert_eq!(machine.registers.get(Register::MM5).value(), 0x0807_0605_0403_0201);
+        assert_eq!(machine.registers.fpu().tag_word(), 0);
+    }
+
+    #[test]
+    fn movd_zero_extends_and_truncates() {
+        let mut machine = Machine { memory: "78 56 34 12 FF".parse().unwrap(), ..Default::default() };
+        machine.registers.set(Register::MM0, u64x1::new(u64::MAX));
+
+        let load = Instruction::new(Mnemonic::Movd, "MM0".parse().unwrap(), Some("[0]".parse().unwrap()), None).unwrap();
+        machine.execute(&load).unwrap();
+        assert_eq!(machine.registers.get(Register::MM0).value(), 0x1234_5678);
+
+        machine.registers.set(Register::MM0, u64x1::new(0xAAAA_AAAA_BBBB_BBBB));
+        let store = Instruction::new(Mnemonic::Movd, "EAX".parse().unwrap(), Some("MM0".parse().unwrap()), None).unwrap();
+        machine.execute(&store).unwrap();
+        assert_eq!(machine.general(GeneralRegister::EAX), 0xBBBB_BBBB);
+    }
+
+    #[test]
+    fn memory_access_outside_memory_fails() {
+        let mut machine = Machine::default();
+        assert!(matches!(machine.execute(&movq("MM0", "[0xF9]")), Err(Error::InvalidArgument(_))));
+        assert!(machine.execute(&movq("[0xF8]", "MM0")).is_ok());
+        assert!(Instruction::new(Mnemonic::Movq, "EAX".parse().unwrap(), Some("MM0".parse().unwrap()), None).is_err());
+    }
+}
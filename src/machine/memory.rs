@@ -0,0 +1,71 @@
+use std::ops::Range;
+use std::str::FromStr;
+use crate::types::u64x1;
+use crate::utils::parse_bytes;
+use crate::error::Error;
+
+pub const MEMORY_SIZE: usize = 256;
+
+/// Byte-addressable memory, registers are stored little-endian like their `u8x8` lanes.
+#[derive(Clone)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct Memory {
+    bytes: [u8; MEMORY_SIZE],
+}
+
+impl Default for Memory {
+    fn default() -> Self {
+        Self { bytes: [0; MEMORY_SIZE] }
+    }
+}
+
+impl Memory {
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    fn range(&self, address: usize, length: usize) -> Result<Range<usize>, Error> {
+        match address.checked_add(length) {
+            Some(end) if end <= MEMORY_SIZE => Ok(address..end),
+            _ => Err(Error::InvalidArgument(format!("Adresa {:#X} je mimo simulovanou paměť o velikosti {} bajtů.", address, MEMORY_SIZE))),
+        }
+    }
+
+    pub fn read_u64(&self, address: usize) -> Result<u64x1, Error> {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(&self.bytes[self.range(address, 8)?]);
+        Ok(u64x1::from_bytes(bytes))
+    }
+
+    pub fn write_u64(&mut self, address: usize, value: u64x1) -> Result<(), Error> {
+        let range = self.range(address, 8)?;
+        self.bytes[range].copy_from_slice(&value.to_bytes());
+        Ok(())
+    }
+
+    pub fn read_u32(&self, address: usize) -> Result<u32, Error> {
+        let mut bytes = [0; 8];
+        bytes[..4].copy_from_slice(&self.bytes[self.range(address, 4)?]);
+        Ok(u64x1::from_bytes(bytes).lane(0, 32) as u32)
+    }
+
+    pub fn write_u32(&mut self, address: usize, value: u32) -> Result<(), Error> {
+        let range = self.range(address, 4)?;
+        self.bytes[range].copy_from_slice(&u64x1::new(value as u64).to_bytes()[..4]);
+        Ok(())
+    }
+}
+
+/// Parses hex bytes stored from address 0, e.g. `01 02 FF`.
+impl FromStr for Memory {
+    type Err = Error;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        let bytes = parse_bytes(src)?;
+
+        let mut memory = Memory::default();
+        let range = memory.range(0, bytes.len())?;
+        memory.bytes[range].copy_from_slice(&bytes);
+        Ok(memory)
+    }
+}
@@ -0,0 +1,8 @@
+#[allow(clippy::module_inception)]
+mod machine;
+mod memory;
+mod operand;
+
+pub use machine::*;
+pub use memory::*;
+pub use operand::*;
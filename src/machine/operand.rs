@@ -0,0 +1,110 @@
+#![allow(clippy::upper_case_acronyms)]
+
+use std::fmt;
+use fmt::{Display, Formatter};
+use std::str::FromStr;
+use strum_macros::{EnumIter, EnumString};
+use crate::registers::Register;
+use crate::utils::parse_number;
+use crate::error::Error;
+
+#[derive(EnumIter, EnumString, strum_macros::Display, Clone, Copy, PartialEq)]
+#[cfg_attr(any(feature = "debug", feature = "gui"), derive(Debug))]
+pub enum GeneralRegister {
+    EAX,
+    ECX,
+    EDX,
+    EBX,
+    ESP,
+    EBP,
+    ESI,
+    EDI,
+}
+
+/// Memory operand `[base + displacement]`.
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(any(feature = "debug", feature = "gui"), derive(Debug))]
+pub struct Address {
+    pub base: Option<GeneralRegister>,
+    pub displacement: u32,
+}
+
+impl Display for Address {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match (self.base, self.displacement as i32) {
+            (Some(base), 0) => write!(f, "[{}]", base),
+            (Some(base), displacement) if displacement < 0 => write!(f, "[{}-{:#X}]", base, displacement.unsigned_abs()),
+            (Some(base), _) => write!(f, "[{}+{:#X}]", base, self.displacement),
+            (None, _) => write!(f, "[{:#X}]", self.displacement),
+        }
+    }
+}
+
+impl FromStr for Address {
+    type Err = Error;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        let mut address = Address { base: None, displacement: 0 };
+
+        for term in src.replace('-', "+-").split('+').map(str::trim).filter(|term| !term.is_empty()) {
+            if let Ok(register) = GeneralRegister::from_str(&term.to_uppercase()) {
+                if address.base.replace(register).is_some() {
+                    return Err(Error::Parse(format!("Adresa \"{}\" smí obsahovat jen jeden registr.", src)));
+                }
+            } else {
+                let (negative, number) = match term.strip_prefix('-') {
+                    Some(number) => (true, number.trim()),
+                    None => (false, term),
+                };
+                let number = parse_number(number)
+                    .map_err(|_| Error::Parse(format!("Neplatná část adresy \"{}\".", term)))? as u32;
+                address.displacement = if negative {
+                    address.displacement.wrapping_sub(number)
+                } else {
+                    address.displacement.wrapping_add(number)
+                };
+            }
+        }
+
+        Ok(address)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(any(feature = "debug", feature = "gui"), derive(Debug))]
+pub enum Operand {
+    Mmx(Register),
+    General(GeneralRegister),
+    Memory(Address),
+}
+
+impl Display for Operand {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Operand::Mmx(register) => write!(f, "{}", register),
+            Operand::General(register) => write!(f, "{}", register),
+            Operand::Memory(address) => write!(f, "{}", address),
+        }
+    }
+}
+
+impl FromStr for Operand {
+    type Err = Error;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        let src = src.trim();
+
+        if let Some(address) = src.strip_prefix('[').and_then(|address| address.strip_suffix(']')) {
+            return address.parse().map(Operand::Memory);
+        }
+
+        let name = src.to_uppercase();
+        if let Ok(register) = Register::from_str(&name) {
+            Ok(Operand::Mmx(register))
+        } else if let Ok(register) = GeneralRegister::from_str(&name) {
+            Ok(Operand::General(register))
+        } else {
+            Err(Error::Parse(format!("Neznámý operand \"{}\".", src)))
+        }
+    }
+}
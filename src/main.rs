@@ -2,10 +2,6 @@
 
 #[cfg(all(feature = "gui", feature = "cli"))]
 compile_error!("features `gui` and `cli` are can't be used together");
-mod types;
-mod calculations;
-mod utils;
-
 
 #[cfg(feature = "cli")]
 mod cli;
@@ -0,0 +1,335 @@
+use core::fmt;
+use fmt::{Debug, Display, Formatter};
+use alloc::format;
+#[cfg(feature = "std")]
+use std::sync::RwLock;
+use crate::types::*;
+use crate::calculations::Lanes;
+use crate::error::Error;
+#[cfg(feature = "std")]
+use core::str::FromStr;
+#[cfg(feature = "std")]
+use crate::registers::Register;
+#[cfg(feature = "std")]
+use crate::machine::GeneralRegister;
+
+/// A lane-wise instruction, implement it and `register` it to add a custom one.
+pub trait Operation: Sync {
+    /// Mnemonic, matched case-insensitively when parsing.
+    fn name(&self) -> &str;
+
+    /// Also describes the equivalent x86 instruction used for encoding, intrinsics and code generation, if there is one.
+    fn metadata(&self) -> &Metadata;
+
+    /// Unmasked result of `mm1`, plus the one of `mm2` for operations writing both registers.
+    /// `mm2` is zero when the source was left out of an operation that doesn't require it.
+    fn evaluate(&self, mm1: u64x1, mm2: u64x1) -> Result<(Lanes, Option<Lanes>), Error>;
+
+    /// The built-in operation this is, for code generation which only knows those.
+    fn builtin(&self) -> Option<Operations> {
+        None
+    }
+
+    fn requires_mm2(&self) -> bool {
+        self.metadata().operands == 2
+    }
+}
+
+impl Display for dyn Operation + '_ {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl Debug for dyn Operation + '_ {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Same name, so a custom operation can't shadow another one.
+impl PartialEq for dyn Operation + '_ {
+    fn eq(&self, other: &Self) -> bool {
+        self.name().eq_ignore_ascii_case(other.name())
+    }
+}
+
+static BUILTIN: [Operations; 19] = Operations::ALL;
+
+#[cfg(feature = "std")]
+static CUSTOM: RwLock<Vec<&'static dyn Operation>> = RwLock::new(Vec::new());
+
+pub fn builtin(operation: Operations) -> &'static dyn Operation {
+    &BUILTIN[operation as usize]
+}
+
+/// Built-in operations first, then the custom ones in the order they were registered.
+#[cfg(feature = "std")]
+pub fn operations() -> Vec<&'static dyn Operation> {
+    let custom = CUSTOM.read().unwrap_or_else(|error| error.into_inner());
+    BUILTIN.iter().map(|operation| operation as &'static dyn Operation).chain(custom.iter().copied()).collect()
+}
+
+/// Finds an operation by its name or the mnemonic of its equivalent instruction, e.g. `PADDUSB` is `PADDSB`.
+#[cfg(feature = "std")]
+pub fn find(name: &str) -> Option<&'static dyn Operation> {
+    operations().into_iter().find(|operation| is_named(*operation, name.trim()))
+}
+
+#[cfg(feature = "std")]
+fn is_named(operation: &dyn Operation, name: &str) -> bool {
+    operation.name().eq_ignore_ascii_case(name)
+        || operation.metadata().hardware.as_ref().is_some_and(|hardware| hardware.mnemonic.eq_ignore_ascii_case(name))
+}
+
+/// Checks that `register` would accept an operation of this name.
+#[cfg(feature = "std")]
+pub fn validate_name(name: &str) -> Result<(), Error> {
+    validate_name_against(name, &CUSTOM.read().unwrap_or_else(|error| error.into_inner()))
+}
+
+#[cfg(feature = "std")]
+fn validate_name_against(name: &str, custom: &[&'static dyn Operation]) -> Result<(), Error> {
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric()) || !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return Err(Error::Parse(format!("Neplatný název operace \"{}\", povolena jsou jen písmena a číslice.", name)));
+    }
+    let taken = BUILTIN.iter().map(|operation| operation as &dyn Operation).chain(custom.iter().copied()).any(|operation| is_named(operation, name));
+    if ["MOVD", "MOVQ", "EMMS"].iter().any(|reserved| reserved.eq_ignore_ascii_case(name)) || taken {
+        return Err(Error::InvalidArgument(format!("Operace {} už existuje.", name)));
+    }
+    // A mnemonic named like a register would make `MM0 MM1, MM2` ambiguous to read.
+    let upper = name.to_uppercase();
+    if Register::from_str(&upper).is_ok() || GeneralRegister::from_str(&upper).is_ok() {
+        return Err(Error::InvalidArgument(format!("Název operace {} je název registru.", name)));
+    }
+    Ok(())
+}
+
+/// Adds an operation for the rest of the program, its name must be a new mnemonic.
+#[cfg(feature = "std")]
+pub fn register(operation: Box<dyn Operation>) -> Result<&'static dyn Operation, Error> {
+    register_all(vec![operation]).map(|operations| operations[0])
+}
+
+/// Adds all the operations or none of them, a clash on the last one leaves the earlier ones out too.
+#[cfg(feature = "std")]
+pub fn register_all(operations: Vec<Box<dyn Operation>>) -> Result<Vec<&'static dyn Operation>, Error> {
+    // Names are checked under the write lock, so another thread can't take one in between.
+    let mut custom = CUSTOM.write().unwrap_or_else(|error| error.into_inner());
+    for (index, operation) in operations.iter().enumerate() {
+        validate_name_against(operation.name(), &custom)?;
+        if operations[..index].iter().any(|previous| previous.name().eq_ignore_ascii_case(operation.name())) {
+            return Err(Error::InvalidArgument(format!("Operace {} je definována vícekrát.", operation.name())));
+        }
+    }
+
+    let operations = operations.into_iter().map(|operation| &*Box::leak(operation)).collect::<Vec<&'static dyn Operation>>();
+    custom.extend(operations.iter().copied());
+    Ok(operations)
+}
+
+impl Operation for Operations {
+    fn name(&self) -> &str {
+        Operations::name(self)
+    }
+
+    fn metadata(&self) -> &Metadata {
+        Operations::metadata(self)
+    }
+
+    fn evaluate(&self, mm1: u64x1, mm2: u64x1) -> Result<(Lanes, Option<Lanes>), Error> {
+        Ok(self.apply(mm1, mm2))
+    }
+
+    fn builtin(&self) -> Option<Operations> {
+        Some(*self)
+    }
+}
+
+impl Operations {
+    /// Unmasked result of `mm1`, plus the one of `mm2` for `PINC*`, usable in constants through `mmx!`.
+    pub const fn apply(self, mm1: u64x1, mm2: u64x1) -> (Lanes, Option<Lanes>) {
+        let (b1, b2) = (u8x8::from_u64x1(mm1), u8x8::from_u64x1(mm2));
+        let (w1, w2) = (u16x4::from_u64x1(mm1), u16x4::from_u64x1(mm2));
+        let (d1, d2) = (u32x2::from_u64x1(mm1), u32x2::from_u64x1(mm2));
+
+        match self {
+            Operations::PADDB => (bytes(b1.overflowing_add(&b2)), None),
+            Operations::PADDSB => (bytes(b1.saturating_add(&b2)), None),
+            Operations::PINCB => (bytes(b1.overflowing_increment()), Some(bytes(b2.overflowing_increment()))),
+            Operations::PINCSB => (bytes(b1.saturating_increment()), Some(bytes(b2.saturating_increment()))),
+            Operations::PMULLB => (words(b1.multiply(&b2)), None),
+
+            Operations::PADDW => (words(w1.overflowing_add(&w2)), None),
+            Operations::PADDSW => (words(w1.saturating_add(&w2)), None),
+            Operations::PINCW => (words(w1.overflowing_increment()), Some(words(w2.overflowing_increment()))),
+            Operations::PINCSW => (words(w1.saturating_increment()), Some(words(w2.saturating_increment()))),
+            Operations::PMULLW => (doublewords(w1.multiply(&w2)), None),
+
+            Operations::PADDD => (doublewords(d1.overflowing_add(&d2)), None),
+            Operations::PADDSD => (doublewords(d1.saturating_add(&d2)), None),
+            Operations::PINCD => (doublewords(d1.overflowing_increment()), Some(doublewords(d2.overflowing_increment()))),
+            Operations::PINCSD => (doublewords(d1.saturating_increment()), Some(doublewords(d2.saturating_increment()))),
+            Operations::PMULLD => (d1.multiply(&d2), None),
+
+            Operations::PADDQ => (mm1.overflowing_add(&mm2), None),
+            Operations::PADDSQ => (mm1.saturating_add(&mm2), None),
+            Operations::PINCQ => (mm1.overflowing_increment(), Some(mm2.overflowing_increment())),
+            Operations::PINCSQ => (mm1.saturating_increment(), Some(mm2.saturating_increment())),
+        }
+    }
+}
+
+/// Evaluates a built-in operation at compile time to a `u64`, `mm2` may be left out for `PINC*`.
+///
+/// ```
+/// use kalkulacka_hw::mmx;
+///
+/// const SATURATED: u64 = mmx!(PADDSB, 0x00FF_00FF_00FF_00FF, 0x0101_0101_0101_0101);
+/// assert_eq!(SATURATED, 0x01FF_01FF_01FF_01FF);
+/// assert_eq!(mmx!(PINCW, 0xFFFF), 0x0001_0001_0001_0000);
+/// ```
+#[macro_export]
+macro_rules! mmx {
+    ($operation:ident, $mm1:expr, $mm2:expr $(,)?) => {{
+        const RESULT: u64 = $crate::types::Operations::$operation
+            .apply($crate::types::u64x1::new($mm1), $crate::types::u64x1::new($mm2)).0 .0
+            .value();
+        RESULT
+    }};
+    ($operation:ident, $mm1:expr $(,)?) => {
+        $crate::mmx!($operation, $mm1, 0)
+    };
+}
+
+const fn bytes((lanes, flags): (u8x8, LaneFlags)) -> Lanes {
+    (lanes.to_u64x1(), flags)
+}
+
+const fn words((lanes, flags): (u16x4, LaneFlags)) -> Lanes {
+    (lanes.to_u64x1(), flags)
+}
+
+const fn doublewords((lanes, flags): (u32x2, LaneFlags)) -> Lanes {
+    (lanes.to_u64x1(), flags)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calculations::{calculate, EitherRegisters};
+
+    #[test]
+    fn builtin_metadata_matches_mnemonic() {
+        for (index, operation) in BUILTIN.iter().enumerate() {
+            assert_eq!(*operation as usize, index);
+
+            let metadata = Operation::metadata(operation);
+            let suffix = match metadata.width {
+                LaneWidth::Byte => 'B',
+                LaneWidth::Word => 'W',
+                LaneWidth::Doubleword => 'D',
+                LaneWidth::Quadword => 'Q',
+            };
+            assert!(operation.name().ends_with(suffix), "{}", operation.name());
+            assert_eq!(operation.name().starts_with("PINC"), !operation.requires_mm2());
+        }
+    }
+
+    const SAMPLES: [(u64, u64); 4] = [
+        (0, 0),
+        (0x7F80_FFFF_0001_00FF, 0x0180_0001_FFFF_0101),
+        (0xFFFF_FFFF_FFFF_FFFF, 0x0000_0000_0000_0001),
+        (0x0123_4567_89AB_CDEF, 0xFEDC_BA98_7654_3210),
+    ];
+
+    #[test]
+    fn mmx_matches_calculate() {
+        macro_rules! check {
+            ($($operation:ident)*) => {
+                assert_eq!([$(stringify!($operation)),*].len(), BUILTIN.len());
+                $(
+                    let results = [
+                        mmx!($operation, SAMPLES[0].0, SAMPLES[0].1),
+                        mmx!($operation, SAMPLES[1].0, SAMPLES[1].1),
+                        mmx!($operation, SAMPLES[2].0, SAMPLES[2].1),
+                        mmx!($operation, SAMPLES[3].0, SAMPLES[3].1),
+                    ];
+                    for ((mm1, mm2), result) in SAMPLES.iter().zip(results.iter()) {
+                        let calculation = calculate(u64x1::new(*mm1), Some(u64x1::new(*mm2)), builtin(Operations::$operation), None).unwrap();
+                        let expected = match calculation.registers {
+                            EitherRegisters::OneRegister(x) | EitherRegisters::TwoRegisters(x, _) => x,
+                        };
+                        assert_eq!(*result, expected.value(), "{} {:016X} {:016X}", stringify!($operation), mm1, mm2);
+                    }
+                )*
+            };
+        }
+
+        check!(
+            PADDB PADDSB PINCB PINCSB PMULLB
+            PADDW PADDSW PINCW PINCSW PMULLW
+            PADDD PADDSD PINCD PINCSD PMULLD
+            PADDQ PADDSQ PINCQ PINCSQ
+        );
+        assert_eq!(mmx!(PINCSB, 0x00FF), 0x0101_0101_0101_01FF);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn custom_operations_are_found_by_name() {
+        struct Swap;
+
+        impl Operation for Swap {
+            fn name(&self) -> &str {
+                "PSWAPQ"
+            }
+
+            fn metadata(&self) -> &Metadata {
+                Operations::PADDQ.metadata()
+            }
+
+            fn evaluate(&self, mm1: u64x1, mm2: u64x1) -> Result<(Lanes, Option<Lanes>), Error> {
+                Ok(((mm2, LaneFlags::unchanged(64)), Some((mm1, LaneFlags::unchanged(64)))))
+            }
+        }
+
+        register(Box::new(Swap)).unwrap();
+        assert_eq!(find("pswapq").map(|operation| operation.name()), Some("PSWAPQ"));
+        assert!(register(Box::new(Swap)).is_err());
+        assert!(find("PADDB").and_then(|operation| operation.builtin()) == Some(Operations::PADDB));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn names_must_be_new_mnemonics() {
+        for name in ["PADDB", "paddusb", "MOVQ", "MM0", "mm7", "EAX", "esp", "1PX", "P-X", ""].iter() {
+            assert!(validate_name(name).is_err(), "{}", name);
+        }
+        assert!(validate_name("PAVGB").is_ok());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn concurrent_registration_takes_a_name_once() {
+        struct Race;
+
+        impl Operation for Race {
+            fn name(&self) -> &str {
+                "PRACEB"
+            }
+
+            fn metadata(&self) -> &Metadata {
+                Operations::PINCB.metadata()
+            }
+
+            fn evaluate(&self, mm1: u64x1, mm2: u64x1) -> Result<(Lanes, Option<Lanes>), Error> {
+                Operations::PINCB.evaluate(mm1, mm2)
+            }
+        }
+
+        let threads = (0..8).map(|_| std::thread::spawn(|| register(Box::new(Race)).is_ok())).collect::<Vec<_>>();
+        let registered = threads.into_iter().map(|thread| thread.join().unwrap()).filter(|registered| *registered).count();
+        assert_eq!(registered, 1);
+    }
+}
@@ -0,0 +1,7 @@
+#[allow(clippy::module_inception)]
+mod program;
+mod parser;
+mod syntax;
+
+pub use program::*;
+pub use syntax::*;
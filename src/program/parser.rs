@@ -0,0 +1,201 @@
+use std::str::FromStr;
+use crate::calculations::{Mask, MaskMode};
+use crate::machine::*;
+use crate::program::Syntax;
+use crate::utils::parse_mask;
+use crate::error::Error;
+
+/// Parses one instruction in Intel syntax, e.g. `PADDB MM1{k=0F}{z}, MM2` or `MOVQ MM0, qword ptr [EAX+8]`,
+/// or in AT&T syntax, e.g. `paddb %mm2, %mm1{k=0F}` or `movq 8(%eax), %mm0`. The syntax is detected from the `%` prefixes.
+impl FromStr for Instruction {
+    type Err = Error;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        parse_instruction(src, Syntax::detect(src))
+    }
+}
+
+fn parse_instruction(src: &str, syntax: Syntax) -> Result<Instruction, Error> {
+    let src = src.trim();
+    let (mnemonic, operands) = match src.find(char::is_whitespace) {
+        Some(index) => (&src[..index], src[index..].trim()),
+        None => (src, ""),
+    };
+
+    let mut operands = split_operands(operands);
+    if syntax == Syntax::Att {
+        operands.reverse();
+    }
+
+    if mnemonic.eq_ignore_ascii_case("EMMS") {
+        return if operands.is_empty() {
+            Ok(Instruction::Emms)
+        } else {
+            Err(Error::InvalidArgument("Instrukce EMMS nemá operandy.".to_string()))
+        };
+    }
+
+    let name = mnemonic;
+    let mnemonic = Mnemonic::from_str(name)?;
+    if let (Syntax::Att, Mnemonic::Operation(operation)) = (syntax, mnemonic) {
+        // Assembler listings mean the real instruction, e.g. the signed saturating `paddsb`.
+        let metadata = operation.metadata();
+        if let (Some(homonym), Some(hardware)) = (&metadata.homonym, &metadata.hardware) {
+            if operation.name().eq_ignore_ascii_case(name) {
+                return Err(Error::Parse(format!(
+                    "Instrukce {} {}, kalkulačka ji nepodporuje. Operaci {} zapište jako {}.",
+                    name, homonym.description, operation, hardware.mnemonic.to_lowercase(),
+                )));
+            }
+        }
+    }
+    let (destination, mask) = match operands.first() {
+        Some(destination) => parse_destination(destination, syntax)?,
+        None => return Err(Error::MissingOperand(format!("Instrukce {} vyžaduje cílový operand.", mnemonic))),
+    };
+    let source = operands.get(1).map(|source| parse_operand(source, syntax)).transpose()?;
+    if operands.len() > 2 {
+        return Err(Error::InvalidArgument(format!("Instrukce {} má nejvýše dva operandy.", mnemonic)));
+    }
+
+    Instruction::new(mnemonic, destination, source, mask)
+}
+
+/// Splits on commas outside of `[...]`, `(...)` and `{...}`.
+fn split_operands(src: &str) -> Vec<&str> {
+    let mut operands = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+
+    for (index, c) in src.char_indices() {
+        match c {
+            '[' | '(' | '{' => depth += 1,
+            ']' | ')' | '}' => depth -= 1,
+            ',' if depth == 0 => {
+                operands.push(src[start..index].trim());
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    if !src.trim().is_empty() {
+        operands.push(src[start..].trim());
+    }
+
+    operands
+}
+
+fn parse_operand(src: &str, syntax: Syntax) -> Result<Operand, Error> {
+    if syntax == Syntax::Att {
+        return parse_att_operand(src);
+    }
+
+    let upper = src.to_uppercase();
+    let operand = ["QWORD PTR", "DWORD PTR", "QWORD", "DWORD"].iter()
+        .find(|size| upper.starts_with(*size))
+        .map_or(src, |size| &src[size.len()..]);
+
+    operand.parse()
+}
+
+/// `%mm0`, `%eax`, `displacement(%base)` or an absolute address.
+fn parse_att_operand(src: &str) -> Result<Operand, Error> {
+    let src = src.trim();
+
+    if let Some(register) = src.strip_prefix('%') {
+        return match Operand::from_str(register)? {
+            Operand::Memory(_) => Err(Error::Parse(format!("Neznámý operand \"{}\".", src))),
+            operand => Ok(operand),
+        };
+    }
+    if src.starts_with('$') {
+        return Err(Error::Unsupported(format!("Přímé hodnoty jako \"{}\" nejsou podporovány.", src)));
+    }
+
+    let (displacement, base) = match src.find('(') {
+        Some(index) => {
+            let base = src[index + 1..].strip_suffix(')')
+                .and_then(|base| base.trim().strip_prefix('%'))
+                .ok_or_else(|| Error::Parse(format!("Neplatná adresa \"{}\", očekáváno posunutí(%registr).", src)))?;
+            let base = GeneralRegister::from_str(&base.to_uppercase())
+                .map_err(|_| Error::Parse(format!("Neznámý registr \"%{}\".", base)))?;
+            (&src[..index], Some(base))
+        }
+        None => (src, None),
+    };
+
+    let mut address = if displacement.trim().is_empty() {
+        Address { base: None, displacement: 0 }
+    } else {
+        Address::from_str(displacement)?
+    };
+    if address.base.is_some() {
+        return Err(Error::Parse(format!("Registry v syntaxi AT&T začínají znakem %, ne \"{}\".", src)));
+    }
+    address.base = base;
+
+    Ok(Operand::Memory(address))
+}
+
+/// Destination operand with optional `{k=XX}` mask and `{z}` zero-masking decorations.
+fn parse_destination(src: &str, syntax: Syntax) -> Result<(Operand, Option<Mask>), Error> {
+    let (operand, decorations) = match src.find('{') {
+        Some(index) => (&src[..index], &src[index..]),
+        None => (src, ""),
+    };
+
+    let mut bits = None;
+    let mut mode = MaskMode::Merge;
+    for decoration in decorations.split('}').map(str::trim).filter(|decoration| !decoration.is_empty()) {
+        let decoration = decoration.strip_prefix('{')
+            .ok_or_else(|| Error::Parse(format!("Neplatný zápis masky \"{}\".", decorations)))?
+            .trim();
+
+        if decoration.eq_ignore_ascii_case("z") {
+            mode = MaskMode::Zero;
+        } else if let Some(value) = decoration.strip_prefix("k=").or_else(|| decoration.strip_prefix("K=")) {
+            let value = value.trim();
+            let value = value.strip_prefix("0x").or_else(|| value.strip_prefix("0X")).unwrap_or(value);
+            bits = Some(parse_mask(value)?);
+        } else {
+            return Err(Error::Parse(format!("Neznámý modifikátor \"{{{}}}\".", decoration)));
+        }
+    }
+
+    let mask = match (bits, mode) {
+        (Some(bits), mode) => Some(Mask { bits, mode }),
+        (None, MaskMode::Zero) => return Err(Error::MissingOperand("Modifikátor {z} vyžaduje masku {k=XX}.".to_string())),
+        (None, MaskMode::Merge) => None,
+    };
+
+    Ok((parse_operand(operand, syntax)?, mask))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intel_round_trip() {
+        for (src, expected) in [
+            ("paddsb mm1{k=0x0f}{z}, mm2", "PADDSB MM1{k=0F}{z}, MM2"),
+            ("PINCW MM3", "PINCW MM3"),
+            ("MOVQ MM0, qword ptr [eax+8]", "MOVQ MM0, [EAX+0x8]"),
+            ("movq [ebp - 4], mm7", "MOVQ [EBP-0x4], MM7"),
+            ("MOVD dword ptr [0x20], MM1", "MOVD [0x20], MM1"),
+            ("MOVD MM2, ECX", "MOVD MM2, ECX"),
+            ("emms", "EMMS"),
+        ].iter() {
+            let instruction = Instruction::from_str(src).unwrap();
+            assert_eq!(instruction.to_string(), *expected);
+            assert_eq!(Instruction::from_str(expected).unwrap().to_string(), *expected);
+        }
+    }
+
+    #[test]
+    fn invalid_instructions() {
+        for src in ["PADDB MM1", "PADDB MM1, [EAX]", "PADDB EAX, MM1", "MOVQ MM0, EAX", "PADDB MM1{z}, MM2", "EMMS MM0", "PADDB MM1, MM2, MM3", "PSUBB MM1, MM2"].iter() {
+            assert!(Instruction::from_str(src).is_err(), "{}", src);
+        }
+    }
+}
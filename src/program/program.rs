@@ -0,0 +1,122 @@
+use std::str::FromStr;
+use crate::machine::*;
+use crate::error::Error;
+
+/// Sequence of instructions separated by new lines or `/`, comments start with `;` or `#`.
+#[derive(Clone)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct Program {
+    instructions: Vec<Instruction>,
+}
+
+impl Program {
+    pub fn instructions(&self) -> &[Instruction] {
+        &self.instructions
+    }
+
+    pub fn run(&self, machine: &mut Machine) -> Result<(), Error> {
+        for (index, instruction) in self.instructions.iter().enumerate() {
+            machine.execute(instruction)
+                .map_err(|error| error.context(format!("Instrukce {} ({})", index + 1, instruction)))?;
+        }
+        Ok(())
+    }
+
+    /// Runs the program like `run`, recording the machine state after every instruction.
+    /// Execution stops at the first failing instruction, the steps before it are kept.
+    pub fn trace(&self, machine: &mut Machine) -> Trace {
+        let mut trace = Trace { initial: machine.clone(), steps: Vec::new(), error: None };
+
+        for (index, instruction) in self.instructions.iter().enumerate() {
+            if let Err(error) = machine.execute(instruction) {
+                trace.error = Some(error.context(format!("Instrukce {} ({})", index + 1, instruction)));
+                break;
+            }
+            trace.steps.push(Step { instruction: *instruction, machine: machine.clone() });
+        }
+
+        trace
+    }
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct Step {
+    pub instruction: Instruction,
+    pub machine: Machine,
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct Trace {
+    pub initial: Machine,
+    pub steps: Vec<Step>,
+    pub error: Option<Error>,
+}
+
+impl Trace {
+    /// State after `step` instructions, `0` is the initial state, `None` past the last step.
+    pub fn state(&self, step: usize) -> Option<&Machine> {
+        match step {
+            0 => Some(&self.initial),
+            step => self.steps.get(step - 1).map(|step| &step.machine),
+        }
+    }
+}
+
+impl From<Vec<Instruction>> for Program {
+    fn from(instructions: Vec<Instruction>) -> Self {
+        Program { instructions }
+    }
+}
+
+impl FromStr for Program {
+    type Err = Error;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        let mut instructions = Vec::new();
+
+        for (number, line) in src.lines().enumerate() {
+            let line = match line.find(&[';', '#'][..]) {
+                Some(index) => &line[..index],
+                None => line,
+            };
+
+            for instruction in line.split('/').map(str::trim).filter(|instruction| !instruction.is_empty()) {
+                instructions.push(
+                    instruction.parse()
+                        .map_err(|error: Error| error.context(format!("Řádek {}", number + 1)))?
+                );
+            }
+        }
+
+        Ok(Program { instructions })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registers::Register;
+
+    #[test]
+    fn trace_keeps_steps_before_the_failing_instruction() {
+        let program: Program = "MOVQ MM0, [0] ; load\nPINCB MM0 / MOVQ [0xFC], MM0\nEMMS".parse().unwrap();
+        let mut machine = Machine::default();
+        let trace = program.trace(&mut machine);
+
+        assert_eq!(trace.steps.len(), 2);
+        assert_eq!(trace.state(0).unwrap().registers.get(Register::MM0).value(), 0);
+        assert_eq!(trace.state(2).unwrap().registers.get(Register::MM0).value(), 0x0101_0101_0101_0101);
+        assert!(trace.state(trace.steps.len() + 1).is_none());
+        let error = trace.error.unwrap().to_string();
+        assert!(error.starts_with("Instrukce 3 (MOVQ [0xFC], MM0)"), "{}", error);
+        assert!(program.run(&mut Machine::default()).is_err());
+    }
+
+    #[test]
+    fn parse_errors_name_the_line() {
+        let error = Program::from_str("EMMS\n\nPADDB MM0").err().unwrap();
+        assert!(matches!(&error, Error::MissingOperand(message) if message.starts_with("Řádek 3")), "{}", error);
+    }
+}
@@ -0,0 +1,118 @@
+use strum_macros::EnumString;
+use crate::machine::*;
+
+/// Assembly syntax used for printing instructions.
+#[derive(EnumString, strum_macros::Display, Clone, Copy, PartialEq)]
+#[cfg_attr(any(feature = "debug", feature = "gui"), derive(Debug))]
+pub enum Syntax {
+    #[strum(serialize = "intel")]
+    Intel,
+    #[strum(serialize = "att")]
+    Att,
+}
+
+impl Syntax {
+    /// AT&T syntax prefixes every register with `%`, Intel syntax never uses it.
+    pub fn detect(src: &str) -> Self {
+        if src.contains('%') {
+            Syntax::Att
+        } else {
+            Syntax::Intel
+        }
+    }
+}
+
+impl Instruction {
+    /// AT&T syntax uses the lowercase mnemonics of the real instructions and the source operand comes first.
+    pub fn format(&self, syntax: Syntax) -> String {
+        if syntax == Syntax::Intel {
+            return self.to_string();
+        }
+
+        match self {
+            Instruction::Operation { operation, destination, source, mask } => {
+                let destination = format!(
+                    "{}{}",
+                    Operand::Mmx(*destination).format(syntax),
+                    mask.map_or(String::new(), |mask| mask.to_string()),
+                );
+                let mnemonic = operation.metadata().hardware.as_ref()
+                    .map_or_else(|| operation.name(), |hardware| hardware.mnemonic)
+                    .to_lowercase();
+                match source {
+                    Some(source) => format!("{} {}, {}", mnemonic, Operand::Mmx(*source).format(syntax), destination),
+                    None => format!("{} {}", mnemonic, destination),
+                }
+            }
+            Instruction::Movd { destination, source } => format!("movd {}, {}", source.format(syntax), destination.format(syntax)),
+            Instruction::Movq { destination, source } => format!("movq {}, {}", source.format(syntax), destination.format(syntax)),
+            Instruction::Emms => "emms".to_string(),
+        }
+    }
+}
+
+impl Operand {
+    /// AT&T syntax writes memory operands as `displacement(%base)`.
+    pub fn format(&self, syntax: Syntax) -> String {
+        match (syntax, self) {
+            (Syntax::Intel, operand) => operand.to_string(),
+            (Syntax::Att, Operand::Mmx(register)) => format!("%{}", register.to_string().to_lowercase()),
+            (Syntax::Att, Operand::General(register)) => format!("%{}", register.to_string().to_lowercase()),
+            (Syntax::Att, Operand::Memory(address)) => match (address.base, address.displacement as i32) {
+                (Some(base), 0) => format!("(%{})", base.to_string().to_lowercase()),
+                (Some(base), displacement) if displacement < 0 => {
+                    format!("-{:#x}(%{})", displacement.unsigned_abs(), base.to_string().to_lowercase())
+                }
+                (Some(base), _) => format!("{:#x}(%{})", address.displacement, base.to_string().to_lowercase()),
+                (None, _) => format!("{:#x}", address.displacement),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+
+    #[test]
+    fn att_round_trip() {
+        for (att, intel) in [
+            ("paddusb %mm2, %mm1{k=0F}{z}", "PADDSB MM1{k=0F}{z}, MM2"),
+            ("pmuludq %mm6, %mm7", "PMULLD MM7, MM6"),
+            ("pmullw %mm1, %mm0", "PMULLW MM0, MM1"),
+            ("pincw %mm3", "PINCW MM3"),
+            ("movq 0x8(%eax), %mm0", "MOVQ MM0, [EAX+0x8]"),
+            ("movq %mm7, -0x4(%ebp)", "MOVQ [EBP-0x4], MM7"),
+            ("movd %mm1, 0x20", "MOVD [0x20], MM1"),
+            ("movd %ecx, %mm2", "MOVD MM2, ECX"),
+            ("emms", "EMMS"),
+        ].iter() {
+            let instruction: Instruction = att.parse().unwrap();
+            assert_eq!(instruction.format(Syntax::Intel), *intel);
+            assert_eq!(instruction.format(Syntax::Att), *att);
+            let intel: Instruction = intel.parse().unwrap();
+            assert_eq!(intel.format(Syntax::Att), *att);
+        }
+    }
+
+    #[test]
+    fn hardware_mnemonics_are_accepted() {
+        let instruction: Instruction = "PADDUSW MM0, MM1".parse().unwrap();
+        assert_eq!(instruction.to_string(), "PADDSW MM0, MM1");
+        assert!("PADDSB MM0, MM1".parse::<Instruction>().is_ok());
+
+        for att in ["paddsb %mm1, %mm0", "paddsw %mm1, %mm0", "pmulld %mm1, %mm0"].iter() {
+            let error = att.parse::<Instruction>().err().unwrap();
+            assert!(matches!(&error, Error::Parse(message) if message.contains("zapište jako")), "{}", att);
+        }
+    }
+
+    #[test]
+    fn syntax_is_detected_from_percent_signs() {
+        assert!(Syntax::detect("paddb %mm1, %mm0") == Syntax::Att);
+        assert!(Syntax::detect("PADDB MM0, MM1") == Syntax::Intel);
+        assert!("movq $1, %mm0".parse::<Instruction>().is_err());
+        assert!("movq 8(eax), %mm0".parse::<Instruction>().is_err());
+    }
+}
@@ -0,0 +1,119 @@
+use std::fmt;
+use fmt::{Display, Formatter};
+use strum::IntoEnumIterator;
+use crate::registers::Register;
+
+const EMPTY_TAG_WORD: u16 = 0xFFFF;
+
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(any(feature = "debug", feature = "gui"), derive(Debug))]
+pub enum Tag {
+    Valid,
+    Zero,
+    Special,
+    Empty,
+}
+
+impl Display for Tag {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Tag::Valid => "platný",
+            Tag::Zero => "nula",
+            Tag::Special => "speciální",
+            Tag::Empty => "prázdný",
+        })
+    }
+}
+
+/// Part of the x87 state aliased by the MMX registers. Register MMi is the mantissa
+/// of physical x87 register Ri, the exponents hold bits 64 to 79 of Ri.
+#[derive(Clone)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct FpuState {
+    tag_word: u16,
+    exponents: [u16; 8],
+    top: u8,
+}
+
+impl Default for FpuState {
+    fn default() -> Self {
+        Self {
+            tag_word: EMPTY_TAG_WORD,
+            exponents: [0; 8],
+            top: 0,
+        }
+    }
+}
+
+impl FpuState {
+    pub fn tag_word(&self) -> u16 {
+        self.tag_word
+    }
+
+    pub fn top(&self) -> u8 {
+        self.top
+    }
+
+    pub fn tag(&self, register: Register) -> Tag {
+        match (self.tag_word >> (register as usize * 2)) & 0b11 {
+            0b00 => Tag::Valid,
+            0b01 => Tag::Zero,
+            0b10 => Tag::Special,
+            _ => Tag::Empty,
+        }
+    }
+
+    pub fn exponent(&self, register: Register) -> u16 {
+        self.exponents[register as usize]
+    }
+
+    /// Every MMX instruction marks the whole stack as valid and resets TOP.
+    pub(super) fn enter_mmx(&mut self) {
+        self.tag_word = 0;
+        self.top = 0;
+    }
+
+    pub(super) fn write_mmx(&mut self, register: Register) {
+        self.exponents[register as usize] = 0xFFFF;
+    }
+
+    pub(super) fn emms(&mut self) {
+        self.tag_word = EMPTY_TAG_WORD;
+    }
+
+    /// MMX register the next x87 instruction reading ST(`index`) would see instead of a float,
+    /// the calculator doesn't execute x87 instructions, it only checks the state left behind.
+    pub fn tainted_st(&self, index: u8) -> Option<Register> {
+        let register = Register::iter().nth((self.top.wrapping_add(index) & 7) as usize)?;
+        if self.tag(register) == Tag::Empty {
+            None
+        } else {
+            Some(register)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mmx_write_taints_the_stack_until_emms() {
+        let mut fpu = FpuState::default();
+        assert!(fpu.tainted_st(0).is_none());
+
+        fpu.enter_mmx();
+        fpu.write_mmx(Register::MM2);
+        assert_eq!(fpu.tag_word(), 0);
+        assert!(fpu.tag(Register::MM2) == Tag::Valid);
+        assert_eq!((fpu.exponent(Register::MM2), fpu.exponent(Register::MM3)), (0xFFFF, 0));
+        assert!(fpu.tainted_st(0) == Some(Register::MM0));
+        assert!(fpu.tainted_st(10) == Some(Register::MM2));
+        assert!(fpu.tainted_st(u8::MAX) == Some(Register::MM7));
+
+        fpu.emms();
+        assert!(fpu.tag(Register::MM2) == Tag::Empty);
+        assert!(fpu.tainted_st(0).is_none());
+        assert_eq!(fpu.exponent(Register::MM2), 0xFFFF);
+    }
+}
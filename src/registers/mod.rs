@@ -0,0 +1,6 @@
+#[allow(clippy::module_inception)]
+mod registers;
+mod fpu;
+
+pub use registers::*;
+pub use fpu::*;
@@ -0,0 +1,129 @@
+use strum::IntoEnumIterator;
+use strum_macros::{EnumIter, EnumString};
+use crate::types::*;
+use crate::calculations::*;
+use crate::operations::Operation;
+use crate::registers::FpuState;
+use crate::error::Error;
+
+#[cfg(feature = "cli")]
+use strum_macros::EnumVariantNames;
+
+#[derive(EnumIter, EnumString, strum_macros::Display, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "cli", derive(EnumVariantNames))]
+#[cfg_attr(any(feature = "debug", feature = "gui"), derive(Debug))]
+pub enum Register {
+    MM0,
+    MM1,
+    MM2,
+    MM3,
+    MM4,
+    MM5,
+    MM6,
+    MM7,
+}
+
+#[derive(Clone, Default)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct RegisterFile {
+    registers: [u64x1; 8],
+    fpu: FpuState,
+}
+
+impl RegisterFile {
+    pub fn get(&self, register: Register) -> u64x1 {
+        self.registers[register as usize]
+    }
+
+    pub fn set(&mut self, register: Register, value: u64x1) {
+        self.registers[register as usize] = value;
+    }
+
+    pub fn fpu(&self) -> &FpuState {
+        &self.fpu
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item=(Register, u64x1)> + '_ {
+        Register::iter().map(move |register| (register, self.get(register)))
+    }
+
+    /// Executes a lane-wise operation, the result is written into `destination`.
+    /// Operations producing two registers (`PINC*`) also write the second one into `source`.
+    pub fn calculate(&mut self, operation: &dyn Operation, destination: Register, source: Option<Register>, mask: Option<Mask>) -> Result<Calculation, Error> {
+        let calculation = calculate(
+            self.get(destination),
+            source.map(|source| self.get(source)),
+            operation,
+            mask,
+        )?;
+
+        match (&calculation.registers, source) {
+            (EitherRegisters::TwoRegisters(x, y), Some(source)) => {
+                self.mmx_write(destination, *x);
+                self.mmx_write(source, *y);
+            }
+            (EitherRegisters::OneRegister(x), _) | (EitherRegisters::TwoRegisters(x, _), None) => {
+                self.mmx_write(destination, *x);
+            }
+        }
+
+        Ok(calculation)
+    }
+
+    pub fn emms(&mut self) {
+        self.fpu.emms();
+    }
+
+    /// Reads the register as an MMX instruction, which switches the x87 stack into MMX state.
+    pub fn mmx_read(&mut self, register: Register) -> u64x1 {
+        self.fpu.enter_mmx();
+        self.get(register)
+    }
+
+    pub fn mmx_write(&mut self, register: Register, value: u64x1) {
+        self.fpu.enter_mmx();
+        self.fpu.write_mmx(register);
+        self.set(register, value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn result_is_written_into_destination() {
+        let mut registers = RegisterFile::default();
+        registers.set(Register::MM0, u64x1::new(0x01FF));
+        registers.set(Register::MM3, u64x1::new(0x0101));
+
+        registers.calculate(&Operations::PADDB, Register::MM0, Some(Register::MM3), None).unwrap();
+        assert_eq!(registers.get(Register::MM0).value(), 0x0200);
+        assert_eq!(registers.get(Register::MM3).value(), 0x0101);
+    }
+
+    #[test]
+    fn increment_writes_source_too() {
+        let mut registers = RegisterFile::default();
+        registers.set(Register::MM1, u64x1::new(0x00FF));
+        registers.set(Register::MM2, u64x1::new(0x0001));
+
+        registers.calculate(&Operations::PINCSB, Register::MM1, Some(Register::MM2), None).unwrap();
+        assert_eq!(registers.get(Register::MM1).value(), 0x0101_0101_0101_01FF);
+        assert_eq!(registers.get(Register::MM2).value(), 0x0101_0101_0101_0102);
+
+        registers.calculate(&Operations::PINCB, Register::MM4, None, None).unwrap();
+        assert_eq!(registers.get(Register::MM4).value(), 0x0101_0101_0101_0101);
+        assert_eq!(registers.get(Register::MM2).value(), 0x0101_0101_0101_0102);
+    }
+
+    #[test]
+    fn failed_operation_leaves_registers_unchanged() {
+        let mut registers = RegisterFile::default();
+        registers.set(Register::MM0, u64x1::new(7));
+
+        assert!(registers.calculate(&Operations::PADDB, Register::MM0, None, None).is_err());
+        assert_eq!(registers.get(Register::MM0).value(), 7);
+        assert_eq!(registers.fpu().tag_word(), 0xFFFF);
+    }
+}
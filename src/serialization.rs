@@ -0,0 +1,196 @@
+//! Registers are serialized as hexadecimal strings like `00000000000000FF`, operations by their mnemonic.
+//! A `u64x1` field can be serialized as lanes instead, e.g. `#[serde(with = "kalkulacka_hw::serialization::lanes8")]`.
+
+use core::fmt;
+use alloc::string::String;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use crate::types::*;
+use crate::utils::parse_hex;
+
+impl Serialize for u64x1 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for u64x1 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_str(HexVisitor)
+    }
+}
+
+struct HexVisitor;
+
+impl de::Visitor<'_> for HexVisitor {
+    type Value = u64x1;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("registr jako nejvýše 16 šestnáctkových číslic")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<u64x1, E> {
+        let digits = value.strip_prefix("0x").or_else(|| value.strip_prefix("0X")).unwrap_or(value);
+        parse_hex(digits).map(u64x1::new).map_err(E::custom)
+    }
+}
+
+macro_rules! hex_lanes {
+    ($($lanes:ident),*) => {$(
+        impl Serialize for $lanes {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                self.to_u64x1().serialize(serializer)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $lanes {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                u64x1::deserialize(deserializer).map(Self::from)
+            }
+        }
+    )*};
+}
+
+hex_lanes!(u32x2, u16x4, u8x8);
+
+impl Serialize for Operations {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for Operations {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(de::Error::custom)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "LaneFlags")]
+struct LaneFlagsFields {
+    bits: usize,
+    statuses: [LaneStatus; 8],
+}
+
+/// Only the widths of the lane types are accepted, statuses past the last lane are dropped.
+impl<'de> Deserialize<'de> for LaneFlags {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let fields = LaneFlagsFields::deserialize(deserializer)?;
+        match fields.bits {
+            8 | 16 | 32 | 64 => Ok(LaneFlags::new(fields.bits, &fields.statuses[..64 / fields.bits])),
+            bits => Err(de::Error::invalid_value(de::Unexpected::Unsigned(bits as u64), &"šířka lanu 8, 16, 32 nebo 64 bitů")),
+        }
+    }
+}
+
+macro_rules! lanes_module {
+    ($($module:ident: $lane:ty, $bits:expr;)*) => {$(
+        /// Serializes a `u64x1` as an array of its lanes, lane 0 first.
+        pub mod $module {
+            use serde::{Deserialize, Deserializer, Serialize, Serializer};
+            use crate::types::u64x1;
+
+            pub fn serialize<S: Serializer>(value: &u64x1, serializer: S) -> Result<S::Ok, S::Error> {
+                let mut lanes = [0; 64 / $bits];
+                for (index, lane) in lanes.iter_mut().enumerate() {
+                    *lane = value.lane(index, $bits) as $lane;
+                }
+                lanes.serialize(serializer)
+            }
+
+            pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64x1, D::Error> {
+                let lanes = <[$lane; 64 / $bits]>::deserialize(deserializer)?;
+                Ok(lanes.iter().enumerate().fold(u64x1::default(), |value, (index, lane)| value.with_lane(index, $bits, *lane as u64)))
+            }
+        }
+    )*};
+}
+
+lanes_module! {
+    lanes8: u8, 8;
+    lanes16: u16, 16;
+    lanes32: u32, 32;
+    lanes64: u64, 64;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calculations::*;
+    use crate::operations::builtin;
+
+    #[derive(Serialize, Deserialize)]
+    struct Lanes {
+        #[serde(with = "lanes8")]
+        bytes: u64x1,
+        #[serde(with = "lanes16")]
+        words: u64x1,
+        #[serde(with = "lanes32")]
+        doublewords: u64x1,
+        #[serde(with = "lanes64")]
+        quadword: u64x1,
+    }
+
+    #[test]
+    fn registers_are_hex_strings() {
+        let register = u64x1::new(0x0123_4567_89AB_CDEF);
+        let json = serde_json::to_string(&register).unwrap();
+
+        assert_eq!(json, "\"0123456789ABCDEF\"");
+        assert_eq!(serde_json::from_str::<u64x1>(&json).unwrap().value(), register.value());
+        assert_eq!(serde_json::to_string(&u8x8::from(register)).unwrap(), json);
+        assert_eq!(serde_json::from_str::<u16x4>(&json).unwrap().to_u64x1().value(), register.value());
+        assert_eq!(serde_json::from_str::<u64x1>("\"0xff\"").unwrap().value(), 0xFF);
+        assert!(serde_json::from_str::<u64x1>("\"10000000000000000\"").is_err());
+        assert!(serde_json::from_str::<u64x1>("255").is_err());
+    }
+
+    #[test]
+    fn lanes_modules_round_trip() {
+        let register = u64x1::new(0x0123_4567_89AB_CDEF);
+        let lanes = Lanes { bytes: register, words: register, doublewords: register, quadword: register };
+        let json = serde_json::to_string(&lanes).unwrap();
+
+        assert_eq!(json, concat!(
+            r#"{"bytes":[239,205,171,137,103,69,35,1],"#,
+            r#""words":[52719,35243,17767,291],"#,
+            r#""doublewords":[2309737967,19088743],"#,
+            r#""quadword":[81985529216486895]}"#,
+        ));
+        let parsed: Lanes = serde_json::from_str(&json).unwrap();
+        for value in [parsed.bytes, parsed.words, parsed.doublewords, parsed.quadword].iter() {
+            assert_eq!(value.value(), register.value());
+        }
+        assert!(serde_json::from_str::<Lanes>(r#"{"bytes":[1,2],"words":[0,0,0,0],"doublewords":[0,0],"quadword":[0]}"#).is_err());
+    }
+
+    #[test]
+    fn calculation_round_trips() {
+        let calculation = calculate(u64x1::new(0x00FF), Some(u64x1::new(0xFF00)), builtin(Operations::PINCSB), None).unwrap();
+        let json = serde_json::to_string(&calculation).unwrap();
+        let parsed: Calculation = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(serde_json::to_string(&parsed).unwrap(), json);
+        match (parsed.registers, parsed.lanes) {
+            (EitherRegisters::TwoRegisters(x, y), EitherLanes::TwoRegisters(x_lanes, y_lanes)) => {
+                assert_eq!((x.value(), y.value()), (0x0101_0101_0101_01FF, 0x0101_0101_0101_FF01));
+                assert!(x_lanes.status(0) == LaneStatus::SaturatedHigh && y_lanes.status(1) == LaneStatus::SaturatedHigh);
+            }
+            _ => panic!("PINCSB zapisuje oba registry"),
+        }
+        assert_eq!(serde_json::to_string(&Operations::PMULLW).unwrap(), "\"PMULLW\"");
+        assert!(serde_json::from_str::<Operations>("\"PMULLW\"").unwrap() == Operations::PMULLW);
+    }
+
+    #[test]
+    fn lane_flags_reject_other_widths() {
+        let flags = LaneFlags::new(16, &[LaneStatus::Overflowed]);
+        let json = serde_json::to_string(&flags).unwrap();
+        let parsed: LaneFlags = serde_json::from_str(&json).unwrap();
+        assert!(parsed.bits() == 16 && parsed.iter().eq(flags.iter()));
+
+        for bits in [0, 7, 12, 128].iter() {
+            let json = json.replace("\"bits\":16", &format!("\"bits\":{}", bits));
+            assert!(serde_json::from_str::<LaneFlags>(&json).is_err(), "{}", json);
+        }
+    }
+}
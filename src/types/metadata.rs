@@ -0,0 +1,442 @@
+use alloc::borrow::Cow;
+use core::fmt;
+use fmt::{Display, Formatter};
+use crate::types::Operations;
+
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(any(feature = "debug", feature = "gui"), derive(Debug))]
+pub enum Kind {
+    Add,
+    Increment,
+    Multiply,
+    /// Defined by a lane expression loaded at runtime.
+    Expression,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(any(feature = "debug", feature = "gui"), derive(Debug))]
+pub enum LaneWidth {
+    Byte,
+    Word,
+    Doubleword,
+    Quadword,
+}
+
+impl LaneWidth {
+    pub fn bits(&self) -> usize {
+        match self {
+            LaneWidth::Byte => 8,
+            LaneWidth::Word => 16,
+            LaneWidth::Doubleword => 32,
+            LaneWidth::Quadword => 64,
+        }
+    }
+
+    pub fn lanes(&self) -> usize {
+        64 / self.bits()
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(any(feature = "debug", feature = "gui"), derive(Debug))]
+pub enum Signedness {
+    Unsigned,
+    Signed,
+}
+
+impl Display for Signedness {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Signedness::Unsigned => "bez znaménka",
+            Signedness::Signed => "se znaménkem",
+        })
+    }
+}
+
+/// What happens when a lane result doesn't fit its width.
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(any(feature = "debug", feature = "gui"), derive(Debug))]
+pub enum Overflow {
+    Wraparound,
+    Saturation,
+    /// The result lanes are twice as wide, so nothing is lost.
+    Widening,
+}
+
+impl Display for Overflow {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Overflow::Wraparound => "přetečení",
+            Overflow::Saturation => "saturace",
+            Overflow::Widening => "rozšíření",
+        })
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(any(feature = "debug", feature = "gui"), derive(Debug))]
+pub enum Shape {
+    /// The destination register, lanes as wide as the operands.
+    Lanes,
+    /// The destination register, lanes twice as wide as the operands.
+    Widened,
+    /// Both the destination and the source register are written.
+    BothRegisters,
+}
+
+impl Display for Shape {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Shape::Lanes => "cílový registr",
+            Shape::Widened => "cílový registr, dvojnásobné pruhy",
+            Shape::BothRegisters => "cílový i zdrojový registr",
+        })
+    }
+}
+
+/// Everything known about an operation, the built-in ones are evaluated by `kind`, `width` and `overflow`.
+#[cfg_attr(any(feature = "debug", feature = "gui"), derive(Debug))]
+pub struct Metadata {
+    pub kind: Kind,
+    pub width: LaneWidth,
+    pub signedness: Signedness,
+    pub overflow: Overflow,
+    /// Registers the operation requires, `PINC*` need only the destination and increment the source when one is given.
+    pub operands: usize,
+    pub shape: Shape,
+    pub description: Cow<'static, str>,
+    pub pseudocode: Cow<'static, str>,
+    /// Real instruction with the same result, `None` for operations the processor doesn't have.
+    pub hardware: Option<Hardware>,
+    /// Real instruction of the same name which computes something else.
+    pub homonym: Option<Homonym>,
+}
+
+/// An x86 instruction computing the same lanes as an operation, for encoding, code generation and `--verify`.
+#[cfg_attr(any(feature = "debug", feature = "gui"), derive(Debug))]
+pub struct Hardware {
+    /// May differ from the operation's name, e.g. `PADDUSB` is the calculator's unsigned saturating `PADDSB`.
+    pub mnemonic: &'static str,
+    /// Opcode following `0F`, the `66` prefix selects the SSE2 form on XMM registers.
+    pub opcode: u8,
+    /// Intrinsics on `__m64`, the first one is printed, `_m_*` are the older MMX names.
+    pub intrinsics: &'static [&'static str],
+    /// `mmintrin.h`, or `emmintrin.h` for the instructions added by SSE2.
+    pub header: &'static str,
+    /// Intrinsic of the SSE2 form on `__m128i`.
+    pub sse2: &'static str,
+}
+
+/// An x86 instruction named like an operation, e.g. the signed saturating `PADDSB`.
+#[cfg_attr(any(feature = "debug", feature = "gui"), derive(Debug))]
+pub struct Homonym {
+    /// Opcode bytes from `0F` on.
+    pub opcode: &'static [u8],
+    /// What the instruction computes instead, shown when it's decoded or parsed.
+    pub description: &'static str,
+}
+
+/// Ordered like `Operations`, so an operation indexes its own entry.
+pub static METADATA: [Metadata; 19] = [
+    Metadata {
+        kind: Kind::Add,
+        width: LaneWidth::Byte,
+        signedness: Signedness::Unsigned,
+        overflow: Overflow::Wraparound,
+        operands: 2,
+        shape: Shape::Lanes,
+        description: Cow::Borrowed("Sečte pruhy bajtů modulo 2^8."),
+        pseudocode: Cow::Borrowed("mm1[i] = (mm1[i] + mm2[i]) mod 2^8, i = 0..7"),
+        hardware: Some(Hardware {
+            mnemonic: "PADDB",
+            opcode: 0xFC,
+            intrinsics: &["_mm_add_pi8", "_m_paddb"],
+            header: "mmintrin.h",
+            sse2: "_mm_add_epi8",
+        }),
+        homonym: None,
+    },
+    Metadata {
+        kind: Kind::Add,
+        width: LaneWidth::Byte,
+        signedness: Signedness::Unsigned,
+        overflow: Overflow::Saturation,
+        operands: 2,
+        shape: Shape::Lanes,
+        description: Cow::Borrowed("Sečte pruhy bajtů se saturací na maximum."),
+        pseudocode: Cow::Borrowed("mm1[i] = min(mm1[i] + mm2[i], FFh), i = 0..7"),
+        hardware: Some(Hardware {
+            mnemonic: "PADDUSB",
+            opcode: 0xDC,
+            intrinsics: &["_mm_adds_pu8", "_m_paddusb"],
+            header: "mmintrin.h",
+            sse2: "_mm_adds_epu8",
+        }),
+        homonym: Some(Homonym {
+            opcode: &[0x0F, 0xEC],
+            description: "sčítá bajty se znaménkem se saturací na -128 a 127",
+        }),
+    },
+    Metadata {
+        kind: Kind::Increment,
+        width: LaneWidth::Byte,
+        signedness: Signedness::Unsigned,
+        overflow: Overflow::Wraparound,
+        operands: 1,
+        shape: Shape::BothRegisters,
+        description: Cow::Borrowed("Zvýší o jedna pruhy bajtů cílového i zdrojového registru modulo 2^8."),
+        pseudocode: Cow::Borrowed("mm1[i] = (mm1[i] + 1) mod 2^8; mm2[i] = (mm2[i] + 1) mod 2^8, i = 0..7"),
+        hardware: None,
+        homonym: None,
+    },
+    Metadata {
+        kind: Kind::Increment,
+        width: LaneWidth::Byte,
+        signedness: Signedness::Unsigned,
+        overflow: Overflow::Saturation,
+        operands: 1,
+        shape: Shape::BothRegisters,
+        description: Cow::Borrowed("Zvýší o jedna pruhy bajtů cílového i zdrojového registru se saturací na maximum."),
+        pseudocode: Cow::Borrowed("mm1[i] = min(mm1[i] + 1, FFh); mm2[i] = min(mm2[i] + 1, FFh), i = 0..7"),
+        hardware: None,
+        homonym: None,
+    },
+    Metadata {
+        kind: Kind::Multiply,
+        width: LaneWidth::Byte,
+        signedness: Signedness::Unsigned,
+        overflow: Overflow::Widening,
+        operands: 2,
+        shape: Shape::Widened,
+        description: Cow::Borrowed("Vynásobí sudé pruhy bajtů, součiny mají dvojnásobnou šířku."),
+        pseudocode: Cow::Borrowed("mm1[i] = mm1[2i] * mm2[2i] (16 bitů), i = 0..3"),
+        hardware: None,
+        homonym: None,
+    },
+    Metadata {
+        kind: Kind::Add,
+        width: LaneWidth::Word,
+        signedness: Signedness::Unsigned,
+        overflow: Overflow::Wraparound,
+        operands: 2,
+        shape: Shape::Lanes,
+        description: Cow::Borrowed("Sečte pruhy slov modulo 2^16."),
+        pseudocode: Cow::Borrowed("mm1[i] = (mm1[i] + mm2[i]) mod 2^16, i = 0..3"),
+        hardware: Some(Hardware {
+            mnemonic: "PADDW",
+            opcode: 0xFD,
+            intrinsics: &["_mm_add_pi16", "_m_paddw"],
+            header: "mmintrin.h",
+            sse2: "_mm_add_epi16",
+        }),
+        homonym: None,
+    },
+    Metadata {
+        kind: Kind::Add,
+        width: LaneWidth::Word,
+        signedness: Signedness::Unsigned,
+        overflow: Overflow::Saturation,
+        operands: 2,
+        shape: Shape::Lanes,
+        description: Cow::Borrowed("Sečte pruhy slov se saturací na maximum."),
+        pseudocode: Cow::Borrowed("mm1[i] = min(mm1[i] + mm2[i], FFFFh), i = 0..3"),
+        hardware: Some(Hardware {
+            mnemonic: "PADDUSW",
+            opcode: 0xDD,
+            intrinsics: &["_mm_adds_pu16", "_m_paddusw"],
+            header: "mmintrin.h",
+            sse2: "_mm_adds_epu16",
+        }),
+        homonym: Some(Homonym {
+            opcode: &[0x0F, 0xED],
+            description: "sčítá slova se znaménkem se saturací na -32768 a 32767",
+        }),
+    },
+    Metadata {
+        kind: Kind::Increment,
+        width: LaneWidth::Word,
+        signedness: Signedness::Unsigned,
+        overflow: Overflow::Wraparound,
+        operands: 1,
+        shape: Shape::BothRegisters,
+        description: Cow::Borrowed("Zvýší o jedna pruhy slov cílového i zdrojového registru modulo 2^16."),
+        pseudocode: Cow::Borrowed("mm1[i] = (mm1[i] + 1) mod 2^16; mm2[i] = (mm2[i] + 1) mod 2^16, i = 0..3"),
+        hardware: None,
+        homonym: None,
+    },
+    Metadata {
+        kind: Kind::Increment,
+        width: LaneWidth::Word,
+        signedness: Signedness::Unsigned,
+        overflow: Overflow::Saturation,
+        operands: 1,
+        shape: Shape::BothRegisters,
+        description: Cow::Borrowed("Zvýší o jedna pruhy slov cílového i zdrojového registru se saturací na maximum."),
+        pseudocode: Cow::Borrowed("mm1[i] = min(mm1[i] + 1, FFFFh); mm2[i] = min(mm2[i] + 1, FFFFh), i = 0..3"),
+        hardware: None,
+        homonym: None,
+    },
+    Metadata {
+        kind: Kind::Multiply,
+        width: LaneWidth::Word,
+        signedness: Signedness::Unsigned,
+        overflow: Overflow::Widening,
+        operands: 2,
+        shape: Shape::Widened,
+        description: Cow::Borrowed("Vynásobí sudé pruhy slov, součiny mají dvojnásobnou šířku."),
+        pseudocode: Cow::Borrowed("mm1[i] = mm1[2i] * mm2[2i] (32 bitů), i = 0..1"),
+        hardware: None,
+        homonym: Some(Homonym {
+            opcode: &[0x0F, 0xD5],
+            description: "násobí všechna slova a ponechá dolních 16 bitů součinů",
+        }),
+    },
+    Metadata {
+        kind: Kind::Add,
+        width: LaneWidth::Doubleword,
+        signedness: Signedness::Unsigned,
+        overflow: Overflow::Wraparound,
+        operands: 2,
+        shape: Shape::Lanes,
+        description: Cow::Borrowed("Sečte pruhy dvojslov modulo 2^32."),
+        pseudocode: Cow::Borrowed("mm1[i] = (mm1[i] + mm2[i]) mod 2^32, i = 0..1"),
+        hardware: Some(Hardware {
+            mnemonic: "PADDD",
+            opcode: 0xFE,
+            intrinsics: &["_mm_add_pi32", "_m_paddd"],
+            header: "mmintrin.h",
+            sse2: "_mm_add_epi32",
+        }),
+        homonym: None,
+    },
+    Metadata {
+        kind: Kind::Add,
+        width: LaneWidth::Doubleword,
+        signedness: Signedness::Unsigned,
+        overflow: Overflow::Saturation,
+        operands: 2,
+        shape: Shape::Lanes,
+        description: Cow::Borrowed("Sečte pruhy dvojslov se saturací na maximum."),
+        pseudocode: Cow::Borrowed("mm1[i] = min(mm1[i] + mm2[i], FFFFFFFFh), i = 0..1"),
+        hardware: None,
+        homonym: None,
+    },
+    Metadata {
+        kind: Kind::Increment,
+        width: LaneWidth::Doubleword,
+        signedness: Signedness::Unsigned,
+        overflow: Overflow::Wraparound,
+        operands: 1,
+        shape: Shape::BothRegisters,
+        description: Cow::Borrowed("Zvýší o jedna pruhy dvojslov cílového i zdrojového registru modulo 2^32."),
+        pseudocode: Cow::Borrowed("mm1[i] = (mm1[i] + 1) mod 2^32; mm2[i] = (mm2[i] + 1) mod 2^32, i = 0..1"),
+        hardware: None,
+        homonym: None,
+    },
+    Metadata {
+        kind: Kind::Increment,
+        width: LaneWidth::Doubleword,
+        signedness: Signedness::Unsigned,
+        overflow: Overflow::Saturation,
+        operands: 1,
+        shape: Shape::BothRegisters,
+        description: Cow::Borrowed("Zvýší o jedna pruhy dvojslov cílového i zdrojového registru se saturací na maximum."),
+        pseudocode: Cow::Borrowed("mm1[i] = min(mm1[i] + 1, FFFFFFFFh); mm2[i] = min(mm2[i] + 1, FFFFFFFFh), i = 0..1"),
+        hardware: None,
+        homonym: None,
+    },
+    Metadata {
+        kind: Kind::Multiply,
+        width: LaneWidth::Doubleword,
+        signedness: Signedness::Unsigned,
+        overflow: Overflow::Widening,
+        operands: 2,
+        shape: Shape::Widened,
+        description: Cow::Borrowed("Vynásobí sudé pruhy dvojslov, součiny mají dvojnásobnou šířku."),
+        pseudocode: Cow::Borrowed("mm1 = mm1[0] * mm2[0] (64 bitů)"),
+        hardware: Some(Hardware {
+            mnemonic: "PMULUDQ",
+            opcode: 0xF4,
+            intrinsics: &["_mm_mul_su32"],
+            header: "emmintrin.h",
+            sse2: "_mm_mul_epu32",
+        }),
+        homonym: Some(Homonym {
+            opcode: &[0x0F, 0x38, 0x40],
+            description: "násobí dvojslova registrů XMM (SSE4.1) a ponechá dolních 32 bitů součinů",
+        }),
+    },
+    Metadata {
+        kind: Kind::Add,
+        width: LaneWidth::Quadword,
+        signedness: Signedness::Unsigned,
+        overflow: Overflow::Wraparound,
+        operands: 2,
+        shape: Shape::Lanes,
+        description: Cow::Borrowed("Sečte pruhy čtyřslov modulo 2^64."),
+        pseudocode: Cow::Borrowed("mm1 = (mm1 + mm2) mod 2^64"),
+        hardware: Some(Hardware {
+            mnemonic: "PADDQ",
+            opcode: 0xD4,
+            intrinsics: &["_mm_add_si64"],
+            header: "emmintrin.h",
+            sse2: "_mm_add_epi64",
+        }),
+        homonym: None,
+    },
+    Metadata {
+        kind: Kind::Add,
+        width: LaneWidth::Quadword,
+        signedness: Signedness::Unsigned,
+        overflow: Overflow::Saturation,
+        operands: 2,
+        shape: Shape::Lanes,
+        description: Cow::Borrowed("Sečte pruhy čtyřslov se saturací na maximum."),
+        pseudocode: Cow::Borrowed("mm1 = min(mm1 + mm2, FFFFFFFFFFFFFFFFh)"),
+        hardware: None,
+        homonym: None,
+    },
+    Metadata {
+        kind: Kind::Increment,
+        width: LaneWidth::Quadword,
+        signedness: Signedness::Unsigned,
+        overflow: Overflow::Wraparound,
+        operands: 1,
+        shape: Shape::BothRegisters,
+        description: Cow::Borrowed("Zvýší o jedna pruhy čtyřslov cílového i zdrojového registru modulo 2^64."),
+        pseudocode: Cow::Borrowed("mm1 = (mm1 + 1) mod 2^64; mm2 = (mm2 + 1) mod 2^64"),
+        hardware: None,
+        homonym: None,
+    },
+    Metadata {
+        kind: Kind::Increment,
+        width: LaneWidth::Quadword,
+        signedness: Signedness::Unsigned,
+        overflow: Overflow::Saturation,
+        operands: 1,
+        shape: Shape::BothRegisters,
+        description: Cow::Borrowed("Zvýší o jedna pruhy čtyřslov cílového i zdrojového registru se saturací na maximum."),
+        pseudocode: Cow::Borrowed("mm1 = min(mm1 + 1, FFFFFFFFFFFFFFFFh); mm2 = min(mm2 + 1, FFFFFFFFFFFFFFFFh)"),
+        hardware: None,
+        homonym: None,
+    },
+];
+
+impl Operations {
+    pub fn metadata(&self) -> &'static Metadata {
+        &METADATA[*self as usize]
+    }
+}
+
+impl Display for Metadata {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}bitové pruhy {}, {}, zapisuje {}",
+            self.width.bits(),
+            self.signedness,
+            self.overflow,
+            self.shape,
+        )
+    }
+}
@@ -1,3 +1,6 @@
+#[allow(clippy::module_inception)]
 mod types;
+mod metadata;
 
-pub use types::*;
\ No newline at end of file
+pub use types::*;
+pub use metadata::*;
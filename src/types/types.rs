@@ -1,49 +1,84 @@
-use std::mem;
-use std::convert::TryInto;
-use std::fmt;
+#![allow(non_camel_case_types, clippy::upper_case_acronyms)]
+
+use core::fmt;
+use core::str::FromStr;
 use fmt::{Display, Formatter};
+use alloc::format;
+use crate::error::Error;
 
+#[derive(Clone, Copy, Default, PartialEq)]
 #[cfg_attr(feature = "debug", derive(Debug))]
 pub struct u64x1(u64);
 
 impl From<u32x2> for u64x1 {
     fn from(data: u32x2) -> Self {
-        u64x1(unsafe { mem::transmute::<[u32; 2], u64>(data.0) })
+        data.to_u64x1()
     }
 }
 
 impl From<u16x4> for u64x1 {
     fn from(data: u16x4) -> Self {
-        u64x1(unsafe { mem::transmute::<[u16; 4], u64>(data.0) })
+        data.to_u64x1()
     }
 }
 
 impl From<u8x8> for u64x1 {
     fn from(data: u8x8) -> Self {
-        u64x1(unsafe { mem::transmute::<[u8; 8], u64>(data.0) })
+        data.to_u64x1()
     }
 }
 
 impl u64x1 {
-    pub fn new(data: u64) -> Self {
+    pub const fn new(data: u64) -> Self {
         Self(data)
     }
 
-    pub fn overflowing_add(&self, x: &u64x1) -> u64x1 {
-        u64x1(self.0.overflowing_add(x.0).0)
+    pub const fn value(&self) -> u64 {
+        self.0
+    }
+
+    /// Lane 0 is always the least significant `bits` of the register, as on x86.
+    pub const fn lane(&self, index: usize, bits: usize) -> u64 {
+        (self.0 >> (index * bits)) & lane_mask(bits)
     }
 
-    pub fn saturating_add(&self, x: &u64x1) -> u64x1 {
-        u64x1(self.0.saturating_add(x.0))
+    /// Memory image of the register, byte lane 0 is stored at the lowest address.
+    pub fn to_bytes(self) -> [u8; 8] {
+        u8x8::from(self).0
     }
 
-    pub fn overflowing_increment(&self) -> Self {
-        Self(self.0.overflowing_add(1).0)
+    pub fn from_bytes(bytes: [u8; 8]) -> Self {
+        u8x8(bytes).to_u64x1()
     }
 
-    pub fn saturating_increment(&self) -> Self {
-        Self(self.0.saturating_add(1))
+    pub const fn with_lane(&self, index: usize, bits: usize, value: u64) -> Self {
+        let mask = lane_mask(bits) << (index * bits);
+        Self((self.0 & !mask) | ((value << (index * bits)) & mask))
     }
+
+    pub const fn overflowing_add(&self, x: &u64x1) -> (u64x1, LaneFlags) {
+        let (value, overflowed) = self.0.overflowing_add(x.0);
+        (u64x1(value), LaneFlags::new(64, &[LaneStatus::from_overflow(overflowed)]))
+    }
+
+    pub const fn saturating_add(&self, x: &u64x1) -> (u64x1, LaneFlags) {
+        match self.0.checked_add(x.0) {
+            Some(value) => (u64x1(value), LaneFlags::unchanged(64)),
+            None => (u64x1(u64::MAX), LaneFlags::new(64, &[LaneStatus::SaturatedHigh])),
+        }
+    }
+
+    pub const fn overflowing_increment(&self) -> (Self, LaneFlags) {
+        self.overflowing_add(&u64x1(1))
+    }
+
+    pub const fn saturating_increment(&self) -> (Self, LaneFlags) {
+        self.saturating_add(&u64x1(1))
+    }
+}
+
+const fn lane_mask(bits: usize) -> u64 {
+    if bits == 64 { u64::MAX } else { (1 << bits) - 1 }
 }
 
 impl Display for u64x1 {
@@ -52,12 +87,14 @@ impl Display for u64x1 {
     }
 }
 
+// The lane types loop with `while` so that their arithmetic is `const`, `mmx!` evaluates the operations with it.
+
 #[cfg_attr(feature = "debug", derive(Debug))]
 pub struct u32x2([u32; 2]);
 
 impl From<u64x1> for u32x2 {
     fn from(data: u64x1) -> Self {
-        u32x2(unsafe { mem::transmute::<u64, [u32; 2]>(data.0) })
+        Self::from_u64x1(data)
     }
 }
 
@@ -68,62 +105,72 @@ impl Display for u32x2 {
 }
 
 impl u32x2 {
-    pub fn new(data: [u32; 2]) -> Self {
+    pub const fn new(data: [u32; 2]) -> Self {
         Self(data)
     }
 
-    pub fn to_u64x1(&self) -> u64x1 {
-        u64x1(unsafe { mem::transmute::<[u32; 2], u64>(self.0) })
+    pub const fn from_u64x1(data: u64x1) -> Self {
+        let mut lanes = [0; 2];
+        let mut index = 0;
+        while index < 2 {
+            lanes[index] = data.lane(index, 32) as u32;
+            index += 1;
+        }
+        u32x2(lanes)
     }
 
-    pub fn overflowing_add(&self, x: &u32x2) -> u32x2 {
-        u32x2(
-            self.0.iter()
-                .zip(x.0.iter())
-                .map(|(x, y)| x.overflowing_add(*y).0)
-                .collect::<Vec<_>>()
-                .as_slice()
-                .try_into()
-                .expect("Overflowing addition - Conversion from [u32; X] to [u32; 2] failed.")
-        )
-    }
+    pub const fn to_u64x1(&self) -> u64x1 {
+        let mut register = u64x1(0);
+        let mut index = 0;
+        while index < 2 {
+            register = register.with_lane(index, 32, self.0[index] as u64);
+            index += 1;
+        }
+        register
+    }
+
+    pub const fn overflowing_add(&self, x: &u32x2) -> (u32x2, LaneFlags) {
+        let mut lanes = self.0;
+        let mut statuses = [LaneStatus::Unchanged; 2];
+        let mut index = 0;
+        while index < 2 {
+            let (lane, overflowed) = lanes[index].overflowing_add(x.0[index]);
+            lanes[index] = lane;
+            statuses[index] = LaneStatus::from_overflow(overflowed);
+            index += 1;
+        }
 
-    pub fn saturating_add(&self, x: &u32x2) -> u32x2 {
-        u32x2(
-            self.0.iter()
-                .zip(x.0.iter())
-                .map(|(x, y)| x.saturating_add(*y))
-                .collect::<Vec<_>>()
-                .as_slice()
-                .try_into()
-                .expect("Saturating addition - Conversion from [u32; X] to [u32; 2] failed.")
-        )
+        (u32x2(lanes), LaneFlags::new(32, &statuses))
+    }
+
+    pub const fn saturating_add(&self, x: &u32x2) -> (u32x2, LaneFlags) {
+        let mut lanes = self.0;
+        let mut statuses = [LaneStatus::Unchanged; 2];
+        let mut index = 0;
+        while index < 2 {
+            match lanes[index].checked_add(x.0[index]) {
+                Some(lane) => lanes[index] = lane,
+                None => {
+                    lanes[index] = u32::MAX;
+                    statuses[index] = LaneStatus::SaturatedHigh;
+                }
+            }
+            index += 1;
+        }
+
+        (u32x2(lanes), LaneFlags::new(32, &statuses))
     }
 
-    pub fn overflowing_increment(&self) -> Self {
-        Self(
-            self.0.iter()
-                .map(|x| x.overflowing_add(1).0)
-                .collect::<Vec<_>>()
-                .as_slice()
-                .try_into()
-                .expect("Increment - Conversion from [u32; X] to [u32; 2] failed.")
-        )
+    pub const fn overflowing_increment(&self) -> (Self, LaneFlags) {
+        self.overflowing_add(&Self([1; 2]))
     }
 
-    pub fn saturating_increment(&self) -> Self {
-        Self(
-            self.0.iter()
-                .map(|x| x.saturating_add(1))
-                .collect::<Vec<_>>()
-                .as_slice()
-                .try_into()
-                .expect("Increment - Conversion from [u32; X] to [u32; 2] failed.")
-        )
+    pub const fn saturating_increment(&self) -> (Self, LaneFlags) {
+        self.saturating_add(&Self([1; 2]))
     }
 
-    pub fn multiply(&self, x: &u32x2) -> u64x1 {
-        u64x1(self.0[1] as u64 * x.0[1] as u64)
+    pub const fn multiply(&self, x: &u32x2) -> (u64x1, LaneFlags) {
+        (u64x1(self.0[0] as u64 * x.0[0] as u64), LaneFlags::unchanged(64))
     }
 }
 
@@ -132,7 +179,7 @@ pub struct u16x4([u16; 4]);
 
 impl From<u64x1> for u16x4 {
     fn from(data: u64x1) -> Self {
-        u16x4(unsafe { mem::transmute::<u64, [u16; 4]>(data.0) })
+        Self::from_u64x1(data)
     }
 }
 
@@ -143,71 +190,79 @@ impl Display for u16x4 {
 }
 
 impl u16x4 {
-    pub fn new(data: [u16; 4]) -> Self {
+    pub const fn new(data: [u16; 4]) -> Self {
         Self(data)
     }
 
-    pub fn to_u64x1(&self) -> u64x1 {
-        u64x1(unsafe { mem::transmute::<[u16; 4], u64>(self.0) })
-    }
-
-    pub fn overflowing_add(&self, x: &u16x4) -> u16x4 {
-        u16x4(
-            self.0.iter()
-                .zip(x.0.iter())
-                .map(|(x, y)| x.overflowing_add(*y).0)
-                .collect::<Vec<_>>()
-                .as_slice()
-                .try_into()
-                .expect("Overflowing addition - Conversion from [u16; X] to [u16; 4] failed.")
-        )
-    }
-
-    pub fn saturating_add(&self, x: &u16x4) -> u16x4 {
-        u16x4(
-            self.0.iter()
-                .zip(x.0.iter())
-                .map(|(x, y)| x.saturating_add(*y))
-                .collect::<Vec<_>>()
-                .as_slice()
-                .try_into()
-                .expect("Saturating addition - Conversion from [u16; X] to [u16; 4] failed.")
-        )
-    }
-
-    pub fn overflowing_increment(&self) -> Self {
-        Self(
-            self.0.iter()
-                .map(|x| x.overflowing_add(1).0)
-                .collect::<Vec<_>>()
-                .as_slice()
-                .try_into()
-                .expect("Increment - Conversion from [u16; X] to [u16; 4] failed.")
-        )
-    }
-
-    pub fn saturating_increment(&self) -> Self {
-        Self(
-            self.0.iter()
-                .map(|x| x.saturating_add(1))
-                .collect::<Vec<_>>()
-                .as_slice()
-                .try_into()
-                .expect("Increment - Conversion from [u16; X] to [u16; 4] failed.")
-        )
-    }
-
-    pub fn multiply(&self, x: &u16x4) -> u32x2 {
-        u32x2(
-            self.0.iter()
-                .zip(x.0.iter())
-                .step_by(2)
-                .map(|(x, y)| *x as u32 * *y as u32)
-                .collect::<Vec<_>>()
-                .as_slice()
-                .try_into()
-                .expect("Multiply - Conversion from [u32; X] to [u32; 2] failed.")
-        )
+    pub const fn from_u64x1(data: u64x1) -> Self {
+        let mut lanes = [0; 4];
+        let mut index = 0;
+        while index < 4 {
+            lanes[index] = data.lane(index, 16) as u16;
+            index += 1;
+        }
+        u16x4(lanes)
+    }
+
+    pub const fn to_u64x1(&self) -> u64x1 {
+        let mut register = u64x1(0);
+        let mut index = 0;
+        while index < 4 {
+            register = register.with_lane(index, 16, self.0[index] as u64);
+            index += 1;
+        }
+        register
+    }
+
+    pub const fn overflowing_add(&self, x: &u16x4) -> (u16x4, LaneFlags) {
+        let mut lanes = self.0;
+        let mut statuses = [LaneStatus::Unchanged; 4];
+        let mut index = 0;
+        while index < 4 {
+            let (lane, overflowed) = lanes[index].overflowing_add(x.0[index]);
+            lanes[index] = lane;
+            statuses[index] = LaneStatus::from_overflow(overflowed);
+            index += 1;
+        }
+
+        (u16x4(lanes), LaneFlags::new(16, &statuses))
+    }
+
+    pub const fn saturating_add(&self, x: &u16x4) -> (u16x4, LaneFlags) {
+        let mut lanes = self.0;
+        let mut statuses = [LaneStatus::Unchanged; 4];
+        let mut index = 0;
+        while index < 4 {
+            match lanes[index].checked_add(x.0[index]) {
+                Some(lane) => lanes[index] = lane,
+                None => {
+                    lanes[index] = u16::MAX;
+                    statuses[index] = LaneStatus::SaturatedHigh;
+                }
+            }
+            index += 1;
+        }
+
+        (u16x4(lanes), LaneFlags::new(16, &statuses))
+    }
+
+    pub const fn overflowing_increment(&self) -> (Self, LaneFlags) {
+        self.overflowing_add(&Self([1; 4]))
+    }
+
+    pub const fn saturating_increment(&self) -> (Self, LaneFlags) {
+        self.saturating_add(&Self([1; 4]))
+    }
+
+    pub const fn multiply(&self, x: &u16x4) -> (u32x2, LaneFlags) {
+        let mut lanes = [0; 2];
+        let mut index = 0;
+        while index < 2 {
+            lanes[index] = self.0[index * 2] as u32 * x.0[index * 2] as u32;
+            index += 1;
+        }
+
+        (u32x2(lanes), LaneFlags::unchanged(32))
     }
 }
 
@@ -216,7 +271,7 @@ pub struct u8x8([u8; 8]);
 
 impl From<u64x1> for u8x8 {
     fn from(data: u64x1) -> Self {
-        u8x8(unsafe { mem::transmute::<u64, [u8; 8]>(data.0) })
+        Self::from_u64x1(data)
     }
 }
 
@@ -227,82 +282,158 @@ impl Display for u8x8 {
 }
 
 impl u8x8 {
-    pub fn new(data: [u8; 8]) -> Self {
+    pub const fn new(data: [u8; 8]) -> Self {
         Self(data)
     }
 
-    pub fn to_u64x1(&self) -> u64x1 {
-        u64x1(unsafe { mem::transmute::<[u8; 8], u64>(self.0) })
-    }
-
-    pub fn overflowing_add(&self, x: &u8x8) -> u8x8 {
-        u8x8(
-            self.0.iter()
-                .zip(x.0.iter())
-                .map(|(x, y)| x.overflowing_add(*y).0)
-                .collect::<Vec<_>>()
-                .as_slice()
-                .try_into()
-                .expect("Overflowing addition - Conversion from [u8; X] to [u8; 8] failed.")
-        )
-    }
-
-    pub fn saturating_add(&self, x: &Self) -> Self {
-        Self(
-            self.0.iter()
-                .zip(x.0.iter())
-                .map(|(x, y)| x.saturating_add(*y))
-                .collect::<Vec<_>>()
-                .as_slice()
-                .try_into()
-                .expect("Saturating addition - Conversion from [u8; X] to [u8; 8] failed.")
-        )
-    }
-
-    pub fn overflowing_increment(&self) -> Self {
-        Self(
-            self.0.iter()
-                .map(|x| x.overflowing_add(1).0)
-                .collect::<Vec<_>>()
-                .as_slice()
-                .try_into()
-                .expect("Increment - Conversion from [u8; X] to [u8; 8] failed.")
-        )
-    }
-
-    pub fn saturating_increment(&self) -> Self {
-        Self(
-            self.0.iter()
-                .map(|x| x.saturating_add(1))
-                .collect::<Vec<_>>()
-                .as_slice()
-                .try_into()
-                .expect("Increment - Conversion from [u8; X] to [u8; 8] failed.")
-        )
-    }
-
-    pub fn multiply(&self, x: &Self) -> u16x4 {
-        u16x4(
-            self.0.iter()
-                .zip(x.0.iter())
-                .step_by(2)
-                .map(|(x, y)| *x as u16 * *y as u16)
-                .collect::<Vec<_>>()
-                .as_slice()
-                .try_into()
-                .expect("Multiply - Conversion from [u16; X] to [u16; 4] failed.")
-        )
+    pub const fn from_u64x1(data: u64x1) -> Self {
+        let mut lanes = [0; 8];
+        let mut index = 0;
+        while index < 8 {
+            lanes[index] = data.lane(index, 8) as u8;
+            index += 1;
+        }
+        u8x8(lanes)
+    }
+
+    pub const fn to_u64x1(&self) -> u64x1 {
+        let mut register = u64x1(0);
+        let mut index = 0;
+        while index < 8 {
+            register = register.with_lane(index, 8, self.0[index] as u64);
+            index += 1;
+        }
+        register
+    }
+
+    pub const fn overflowing_add(&self, x: &u8x8) -> (u8x8, LaneFlags) {
+        let mut lanes = self.0;
+        let mut statuses = [LaneStatus::Unchanged; 8];
+        let mut index = 0;
+        while index < 8 {
+            let (lane, overflowed) = lanes[index].overflowing_add(x.0[index]);
+            lanes[index] = lane;
+            statuses[index] = LaneStatus::from_overflow(overflowed);
+            index += 1;
+        }
+
+        (u8x8(lanes), LaneFlags::new(8, &statuses))
+    }
+
+    pub const fn saturating_add(&self, x: &u8x8) -> (u8x8, LaneFlags) {
+        let mut lanes = self.0;
+        let mut statuses = [LaneStatus::Unchanged; 8];
+        let mut index = 0;
+        while index < 8 {
+            match lanes[index].checked_add(x.0[index]) {
+                Some(lane) => lanes[index] = lane,
+                None => {
+                    lanes[index] = u8::MAX;
+                    statuses[index] = LaneStatus::SaturatedHigh;
+                }
+            }
+            index += 1;
+        }
+
+        (u8x8(lanes), LaneFlags::new(8, &statuses))
+    }
+
+    pub const fn overflowing_increment(&self) -> (Self, LaneFlags) {
+        self.overflowing_add(&Self([1; 8]))
+    }
+
+    pub const fn saturating_increment(&self) -> (Self, LaneFlags) {
+        self.saturating_add(&Self([1; 8]))
+    }
+
+    pub const fn multiply(&self, x: &Self) -> (u16x4, LaneFlags) {
+        let mut lanes = [0; 4];
+        let mut index = 0;
+        while index < 4 {
+            lanes[index] = self.0[index * 2] as u16 * x.0[index * 2] as u16;
+            index += 1;
+        }
+
+        (u16x4(lanes), LaneFlags::unchanged(16))
     }
 }
 
-#[cfg(feature = "cli")]
-use strum_macros::{EnumVariantNames, EnumString};
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(any(feature = "debug", feature = "gui"), derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LaneStatus {
+    Unchanged,
+    Overflowed,
+    SaturatedHigh,
+    /// Only custom operations clamp a lane to its minimum, with `sat` or `ssat`, the built-in ones add unsigned lanes.
+    SaturatedLow,
+    Masked,
+}
 
-#[cfg(feature = "gui")]
-use strum_macros::{EnumIter, Display};
+impl LaneStatus {
+    const fn from_overflow(overflowed: bool) -> Self {
+        if overflowed { LaneStatus::Overflowed } else { LaneStatus::Unchanged }
+    }
+}
 
-#[cfg_attr(feature = "cli", derive(EnumString, EnumVariantNames))]
-#[cfg_attr(feature = "gui", derive(EnumIter, Clone, PartialEq, Display))]
+impl Display for LaneStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LaneStatus::Unchanged => "bez přetečení",
+            LaneStatus::Overflowed => "přetečení",
+            LaneStatus::SaturatedHigh => "saturace na maximum",
+            LaneStatus::SaturatedLow => "saturace na minimum",
+            LaneStatus::Masked => "maskováno",
+        })
+    }
+}
+
+/// Status of every lane of a result register, lane 0 first.
+#[derive(Clone, Copy)]
+#[cfg_attr(any(feature = "debug", feature = "gui"), derive(Debug))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LaneFlags {
+    bits: usize,
+    statuses: [LaneStatus; 8],
+}
+
+impl LaneFlags {
+    pub const fn new(bits: usize, statuses: &[LaneStatus]) -> Self {
+        let mut flags = Self { bits, statuses: [LaneStatus::Unchanged; 8] };
+        let mut index = 0;
+        while index < statuses.len() {
+            flags.statuses[index] = statuses[index];
+            index += 1;
+        }
+        flags
+    }
+
+    pub const fn unchanged(bits: usize) -> Self {
+        Self::new(bits, &[])
+    }
+
+    pub fn bits(&self) -> usize {
+        self.bits
+    }
+
+    pub fn lanes(&self) -> usize {
+        64 / self.bits
+    }
+
+    pub fn status(&self, index: usize) -> LaneStatus {
+        self.statuses[index]
+    }
+
+    pub fn set_status(&mut self, index: usize, status: LaneStatus) {
+        self.statuses[index] = status;
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item=LaneStatus> + '_ {
+        self.statuses[..self.lanes()].iter().copied()
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
 #[cfg_attr(any(feature = "debug", feature = "gui"), derive(Debug))]
 pub enum Operations {
     PADDB,
@@ -329,12 +460,91 @@ pub enum Operations {
     PINCSQ,
 }
 
-#[cfg(feature = "gui")]
 impl Operations {
-    pub fn requires_mm2(&self) -> bool {
+    pub const ALL: [Operations; 19] = [
+        Operations::PADDB, Operations::PADDSB, Operations::PINCB, Operations::PINCSB, Operations::PMULLB,
+        Operations::PADDW, Operations::PADDSW, Operations::PINCW, Operations::PINCSW, Operations::PMULLW,
+        Operations::PADDD, Operations::PADDSD, Operations::PINCD, Operations::PINCSD, Operations::PMULLD,
+        Operations::PADDQ, Operations::PADDSQ, Operations::PINCQ, Operations::PINCSQ,
+    ];
+
+    pub fn name(&self) -> &'static str {
         match self {
-            Operations::PINCB | Operations::PINCSB | Operations::PINCW | Operations::PINCSW | Operations::PINCD | Operations::PINCSD | Operations::PINCQ | Operations::PINCSQ => false,
-            _ => true
+            Operations::PADDB => "PADDB",
+            Operations::PADDSB => "PADDSB",
+            Operations::PINCB => "PINCB",
+            Operations::PINCSB => "PINCSB",
+            Operations::PMULLB => "PMULLB",
+            Operations::PADDW => "PADDW",
+            Operations::PADDSW => "PADDSW",
+            Operations::PINCW => "PINCW",
+            Operations::PINCSW => "PINCSW",
+            Operations::PMULLW => "PMULLW",
+            Operations::PADDD => "PADDD",
+            Operations::PADDSD => "PADDSD",
+            Operations::PINCD => "PINCD",
+            Operations::PINCSD => "PINCSD",
+            Operations::PMULLD => "PMULLD",
+            Operations::PADDQ => "PADDQ",
+            Operations::PADDSQ => "PADDSQ",
+            Operations::PINCQ => "PINCQ",
+            Operations::PINCSQ => "PINCSQ",
         }
     }
-}
\ No newline at end of file
+}
+
+impl Display for Operations {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Operations {
+    type Err = Error;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        Operations::ALL.iter()
+            .find(|operation| operation.name() == src)
+            .copied()
+            .ok_or_else(|| Error::Parse(format!("Neznámá operace \"{}\".", src)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lane_zero_is_least_significant() {
+        let register = u64x1::new(0x0807_0605_0403_0201);
+
+        assert_eq!(u8x8::from(u64x1::new(register.0)).0, [1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(u16x4::from(u64x1::new(register.0)).0, [0x0201, 0x0403, 0x0605, 0x0807]);
+        assert_eq!(u32x2::from(u64x1::new(register.0)).0, [0x0403_0201, 0x0807_0605]);
+    }
+
+    #[test]
+    fn lanes_are_inserted_little_endian() {
+        assert_eq!(u8x8::new([1, 2, 3, 4, 5, 6, 7, 8]).to_u64x1().0, 0x0807_0605_0403_0201);
+        assert_eq!(u16x4::new([0x0201, 0x0403, 0x0605, 0x0807]).to_u64x1().0, 0x0807_0605_0403_0201);
+        assert_eq!(u32x2::new([0x0403_0201, 0x0807_0605]).to_u64x1().0, 0x0807_0605_0403_0201);
+    }
+
+    #[test]
+    fn conversions_round_trip() {
+        let value = 0xFEDC_BA98_7654_3210;
+
+        assert_eq!(u64x1::from(u8x8::from(u64x1::new(value))).0, value);
+        assert_eq!(u64x1::from(u16x4::from(u64x1::new(value))).0, value);
+        assert_eq!(u64x1::from(u32x2::from(u64x1::new(value))).0, value);
+    }
+
+    #[test]
+    fn carry_stays_within_lane() {
+        let (result, _) = u8x8::from(u64x1::new(0x0000_0000_0000_00FF))
+            .overflowing_add(&u8x8::from(u64x1::new(0x0000_0000_0000_0001)));
+
+        assert_eq!(result.0, [0, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(u16x4::new([0xFFFF, 1, 2, 3]).overflowing_increment().0.to_u64x1().0, 0x0004_0003_0002_0000);
+    }
+}
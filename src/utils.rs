@@ -1,18 +1,55 @@
-use std::num::ParseIntError;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use crate::error::Error;
 
-#[cfg(feature = "gui")]
-use regex::Regex;
+pub fn parse_hex(src: &str) -> Result<u64, Error> {
+    u64::from_str_radix(src, 16).map_err(|_| Error::Parse(format!("Neplatné šestnáctkové číslo \"{}\".", src)))
+}
+
+pub fn parse_mask(src: &str) -> Result<u8, Error> {
+    u8::from_str_radix(src, 16).map_err(|_| Error::Parse(format!("Neplatná maska \"{}\", očekávány nejvýše dvě šestnáctkové číslice.", src)))
+}
+
+/// Parses hex bytes, whitespace between them is optional, e.g. `0F FC C1` or `0FFCC1`.
+pub fn parse_bytes(src: &str) -> Result<Vec<u8>, Error> {
+    let digits = src.chars().filter(|c| !c.is_whitespace()).collect::<Vec<_>>();
+    if digits.len() % 2 != 0 {
+        return Err(Error::Parse("Bajty musí mít sudý počet šestnáctkových číslic.".to_string()));
+    }
 
-#[cfg(feature = "gui")]
-lazy_static! {
-    static ref REPLACING_REG: Regex = Regex::new(r"[^0-9ABCDEF]").unwrap();
+    digits.chunks(2)
+        .map(|digits| {
+            let digits = digits.iter().collect::<String>();
+            u8::from_str_radix(&digits, 16).map_err(|_| Error::Parse(format!("Neplatný bajt \"{}\".", digits)))
+        })
+        .collect()
 }
 
-#[cfg(feature = "gui")]
-pub fn cleanup_hex_string(value: String) -> String {
-    REPLACING_REG.replace_all(value.to_uppercase().as_str(), "").to_string()
+/// Accepts `0x10`, `10h` and decimal `16`.
+pub fn parse_number(src: &str) -> Result<u64, Error> {
+    let src = src.trim();
+    if let Some(hex) = src.strip_prefix("0x").or_else(|| src.strip_prefix("0X")) {
+        parse_hex(hex)
+    } else if let Some(hex) = src.strip_suffix('h').or_else(|| src.strip_suffix('H')) {
+        parse_hex(hex)
+    } else {
+        src.parse().map_err(|_| Error::Parse(format!("Neplatné číslo \"{}\".", src)))
+    }
 }
 
-pub fn parse_hex(src: &str) -> Result<u64, ParseIntError> {
-    u64::from_str_radix(src, 16)
-}
\ No newline at end of file
+/// Marsaglia's xorshift64, good enough for generating test inputs without a dependency.
+pub struct XorShift64(u64);
+
+impl XorShift64 {
+    pub fn new(seed: u64) -> Self {
+        Self(if seed == 0 { 0x9E37_79B9_7F4A_7C15 } else { seed })
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
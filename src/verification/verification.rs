@@ -0,0 +1,130 @@
+use std::arch::x86_64::*;
+use crate::types::*;
+use crate::calculations::*;
+use crate::utils::XorShift64;
+use crate::error::Error;
+
+/// Lane patterns random inputs rarely hit: zero, all ones and the sign boundaries.
+const EDGE_CASES: [u64; 6] = [
+    0,
+    u64::MAX,
+    0x8080_8080_8080_8080,
+    0x7F7F_7F7F_7F7F_7F7F,
+    0x8000_8000_8000_8000,
+    0x0000_0001_FFFF_FFFF,
+];
+
+/// Differing results, or an error of either side, so a broken operation can't pass unnoticed.
+#[derive(Clone)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct Mismatch {
+    pub a: u64x1,
+    pub b: u64x1,
+    pub emulated: Result<u64x1, Error>,
+    pub hardware: Result<u64x1, Error>,
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "debug", derive(Debug))]
+pub struct Report {
+    pub operation: Operations,
+    /// The instruction and its SSE2 intrinsic the operation was compared with, `None` when it wasn't checked.
+    pub hardware: Option<&'static Hardware>,
+    pub samples: usize,
+    pub mismatches: Vec<Mismatch>,
+}
+
+/// Compares `calculate` with the host CPU on every pair of edge cases and `samples` random inputs.
+/// Operations without an equivalent instruction, e.g. `PMULLW` and `PINC*`, are reported with no samples.
+pub fn verify(samples: usize, seed: u64) -> Vec<Report> {
+    let (checked, unchecked): (Vec<_>, Vec<_>) = Operations::ALL.iter()
+        .partition(|operation| operation.metadata().hardware.is_some());
+
+    let mut reports = verify_operations(&checked, samples, seed);
+    reports.extend(unchecked.into_iter().map(|operation| Report { operation, hardware: None, samples: 0, mismatches: Vec::new() }));
+    reports
+}
+
+fn verify_operations(operations: &[Operations], samples: usize, seed: u64) -> Vec<Report> {
+    let mut random = XorShift64::new(seed);
+    let inputs = EDGE_CASES.iter()
+        .flat_map(|a| EDGE_CASES.iter().map(move |b| (*a, *b)))
+        .chain((0..samples).map(|_| (random.next_u64(), random.next_u64())))
+        .map(|(a, b)| (u64x1::new(a), u64x1::new(b)))
+        .collect::<Vec<_>>();
+
+    operations.iter()
+        .map(|operation| Report {
+            operation: *operation,
+            hardware: operation.metadata().hardware.as_ref(),
+            samples: inputs.len(),
+            mismatches: inputs.iter()
+                .filter_map(|(a, b)| {
+                    let emulated = calculate(*a, Some(*b), operation, None).map(|calculation| match calculation.registers {
+                        EitherRegisters::OneRegister(x) | EitherRegisters::TwoRegisters(x, _) => x,
+                    });
+                    let hardware = hardware(operation, *a, *b);
+                    match (&emulated, &hardware) {
+                        (Ok(emulated), Ok(hardware)) if emulated == hardware => None,
+                        _ => Some(Mismatch { a: *a, b: *b, emulated, hardware }),
+                    }
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+fn hardware(operation: &Operations, a: u64x1, b: u64x1) -> Result<u64x1, Error> {
+    // SSE2 is part of the x86_64 baseline, so the intrinsics are always available.
+    unsafe {
+        let a = _mm_set_epi64x(0, a.lane(0, 64) as i64);
+        let b = _mm_set_epi64x(0, b.lane(0, 64) as i64);
+        // The low 64 bits of the SSE2 form are the MMX result.
+        let result = match operation.metadata().hardware.as_ref().map(|hardware| hardware.sse2) {
+            Some("_mm_add_epi8") => _mm_add_epi8(a, b),
+            Some("_mm_add_epi16") => _mm_add_epi16(a, b),
+            Some("_mm_add_epi32") => _mm_add_epi32(a, b),
+            Some("_mm_add_epi64") => _mm_add_epi64(a, b),
+            Some("_mm_adds_epu8") => _mm_adds_epu8(a, b),
+            Some("_mm_adds_epu16") => _mm_adds_epu16(a, b),
+            Some("_mm_mul_epu32") => _mm_mul_epu32(a, b),
+            _ => return Err(Error::Unsupported(format!("Operace {} nemá instrukci SSE2.", operation))),
+        };
+        Ok(u64x1::new(_mm_cvtsi128_si64(result) as u64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn emulation_matches_hardware() {
+        let reports = verify(200, 1);
+        assert_eq!(reports.len(), Operations::ALL.len());
+        for report in reports.iter().filter(|report| report.hardware.is_some()) {
+            assert!(report.mismatches.is_empty(), "{}", report.operation);
+            assert_eq!(report.samples, EDGE_CASES.len() * EDGE_CASES.len() + 200);
+        }
+    }
+
+    #[test]
+    fn reports_name_the_intrinsic_and_the_unchecked_operations() {
+        let reports = verify(0, 1);
+        let report = |operation: Operations| reports.iter().find(|report| report.operation == operation).unwrap();
+
+        let hardware = report(Operations::PADDSB).hardware.unwrap();
+        assert_eq!((hardware.mnemonic, hardware.sse2), ("PADDUSB", "_mm_adds_epu8"));
+        assert_eq!(report(Operations::PMULLD).hardware.unwrap().sse2, "_mm_mul_epu32");
+        for operation in [Operations::PMULLW, Operations::PINCB, Operations::PINCSQ].iter() {
+            assert!(report(*operation).hardware.is_none() && report(*operation).samples == 0, "{}", operation);
+        }
+    }
+
+    #[test]
+    fn errors_count_as_mismatches() {
+        let reports = verify_operations(&[Operations::PINCB], 10, 1);
+        assert_eq!(reports[0].mismatches.len(), reports[0].samples);
+        assert!(reports[0].mismatches.iter().all(|mismatch| mismatch.emulated.is_ok() && mismatch.hardware.is_err()));
+    }
+}